@@ -0,0 +1,274 @@
+use crate::{
+    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, PositiveDefiniteKernel,
+    ValueDifferentiableKernel,
+};
+use opensrdk_linear_algebra::Matrix;
+use std::cell::RefCell;
+use std::{ops::Add, ops::Mul};
+
+thread_local! {
+    /// Buffers for projected inputs, reused across calls on each thread.
+    static BUFFERS: RefCell<Vec<Vec<f64>>> = const { RefCell::new(vec![]) };
+}
+
+fn take_buffer() -> Vec<f64> {
+    BUFFERS.with(|buffers| buffers.borrow_mut().pop().unwrap_or_default())
+}
+
+fn return_buffer(mut buffer: Vec<f64>) {
+    buffer.clear();
+    BUFFERS.with(|buffers| buffers.borrow_mut().push(buffer));
+}
+
+/// Map from the full input to the input of the inner kernel of `ActiveDims`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Projection {
+    /// Picks the elements at the indices.
+    Indices(Vec<usize>),
+    /// Multiplies by the matrix, whose columns must match the input dimension.
+    Matrix(Matrix),
+}
+
+impl Projection {
+    fn project(&self, x: &[f64], projected: &mut Vec<f64>) -> Result<(), KernelError> {
+        match self {
+            Projection::Indices(indices) => {
+                for &i in indices.iter() {
+                    projected.push(*x.get(i).ok_or(KernelError::InvalidArgument)?);
+                }
+            }
+            Projection::Matrix(p) => {
+                if p.cols() != x.len() {
+                    return Err(KernelError::InvalidArgument);
+                }
+                projected.extend((0..p.rows()).map(|i| {
+                    x.iter()
+                        .enumerate()
+                        .map(|(j, x_j)| p[(i, j)] * x_j)
+                        .sum::<f64>()
+                }));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pulls a gradient with respect to the projected input back to the full input.
+    fn scatter(&self, diff: &[f64], len: usize) -> Vec<f64> {
+        let mut scattered = vec![0.0; len];
+        match self {
+            Projection::Indices(indices) => {
+                for (&i, d) in indices.iter().zip(diff.iter()) {
+                    scattered[i] += d;
+                }
+            }
+            Projection::Matrix(p) => {
+                for (j, s) in scattered.iter_mut().enumerate() {
+                    *s = diff.iter().enumerate().map(|(i, d)| p[(i, j)] * d).sum();
+                }
+            }
+        }
+
+        scattered
+    }
+}
+
+/// Applies the inner kernel to a subset of the input dimensions or to a linear projection of the input.
+///
+/// The projected inputs are written into thread-local buffers, so `value` does not allocate after warm-up.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActiveDims<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+{
+    kernel: K,
+    projection: Projection,
+}
+
+impl<K> ActiveDims<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+{
+    pub fn new(kernel: K, indices: Vec<usize>) -> Self {
+        Self {
+            kernel,
+            projection: Projection::Indices(indices),
+        }
+    }
+
+    /// `k(P x, P x')` for a matrix `P`.
+    pub fn with_projection(kernel: K, projection: Matrix) -> Self {
+        Self {
+            kernel,
+            projection: Projection::Matrix(projection),
+        }
+    }
+
+    pub fn kernel_ref(&self) -> &K {
+        &self.kernel
+    }
+
+    pub fn projection(&self) -> &Projection {
+        &self.projection
+    }
+
+    fn with_projected<R, F>(&self, x: &[f64], xprime: &[f64], f: F) -> Result<R, KernelError>
+    where
+        F: FnOnce(&Vec<f64>, &Vec<f64>) -> Result<R, KernelError>,
+    {
+        let mut z = take_buffer();
+        let mut zprime = take_buffer();
+
+        let result = self
+            .projection
+            .project(x, &mut z)
+            .and_then(|_| self.projection.project(xprime, &mut zprime))
+            .and_then(|_| f(&z, &zprime));
+
+        return_buffer(z);
+        return_buffer(zprime);
+
+        result
+    }
+}
+
+impl<K> PositiveDefiniteKernel<Vec<f64>> for ActiveDims<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+{
+    fn params_len(&self) -> usize {
+        self.kernel.params_len()
+    }
+
+    fn params_names(&self) -> Vec<String> {
+        self.kernel
+            .params_names()
+            .into_iter()
+            .map(|name| format!("active_dims.{}", name))
+            .collect()
+    }
+
+    fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
+        self.with_projected(x, xprime, |z, zprime| self.kernel.value(params, z, zprime))
+    }
+}
+
+impl<K, R> Add<R> for ActiveDims<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelAdd<Self, R, Vec<f64>>;
+
+    fn add(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<K, R> Mul<R> for ActiveDims<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelMul<Self, R, Vec<f64>>;
+
+    fn mul(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<K> ValueDifferentiableKernel<Vec<f64>> for ActiveDims<K>
+where
+    K: ValueDifferentiableKernel<Vec<f64>>,
+{
+    fn ln_diff_value(
+        &self,
+        params: &[f64],
+        x: &Vec<f64>,
+        xprime: &Vec<f64>,
+    ) -> Result<Vec<f64>, KernelError> {
+        let diff = self.with_projected(x, xprime, |z, zprime| {
+            self.kernel.ln_diff_value(params, z, zprime)
+        })?;
+
+        Ok(self.projection.scatter(&diff, x.len()))
+    }
+
+    fn is_value_differentiable(&self) -> bool {
+        self.kernel.is_value_differentiable()
+    }
+}
+
+impl<K> ParamsDifferentiableKernel<Vec<f64>> for ActiveDims<K>
+where
+    K: ParamsDifferentiableKernel<Vec<f64>>,
+{
+    fn ln_diff_params(
+        &self,
+        params: &[f64],
+        x: &Vec<f64>,
+        xprime: &Vec<f64>,
+    ) -> Result<Vec<f64>, KernelError> {
+        self.with_projected(x, xprime, |z, zprime| {
+            self.kernel.ln_diff_params(params, z, zprime)
+        })
+    }
+
+    fn is_params_differentiable(&self) -> bool {
+        self.kernel.is_params_differentiable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::opensrdk_linear_algebra::Matrix;
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let kernel = ActiveDims::new(RBF, vec![0, 1, 2]) + ActiveDims::new(Periodic, vec![3]);
+        let params = [1.0, 0.5, 0.4, 1.1];
+        let x = vec![0.1, 0.5, -0.3, 2.0];
+        let xprime = vec![0.4, -0.2, 0.8, 2.5];
+
+        let expected = RBF
+            .value(&params[0..2], &x[0..3].to_vec(), &xprime[0..3].to_vec())
+            .unwrap()
+            + Periodic
+                .value(&params[2..4], &vec![x[3]], &vec![xprime[3]])
+                .unwrap();
+
+        assert_eq!(kernel.value(&params, &x, &xprime).unwrap(), expected);
+        match ActiveDims::new(RBF, vec![4]).value(&params[0..2], &x, &xprime) {
+            Err(KernelError::InvalidArgument) => (),
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn it_works2() {
+        let mut p = Matrix::new(1, 2);
+        p[(0, 0)] = 1.0;
+        p[(0, 1)] = 1.0;
+        let kernel = ActiveDims::with_projection(Linear, p);
+
+        let test_value = kernel
+            .value(&[], &vec![1.0, 2.0], &vec![3.0, -1.0])
+            .unwrap();
+
+        assert_eq!(test_value, 6.0);
+    }
+
+    #[test]
+    fn check_gradients() {
+        let mut p = Matrix::new(2, 3);
+        p.elems_mut()
+            .copy_from_slice(&[0.5, -0.3, 1.0, 0.2, 0.0, 0.7]);
+        let kernel = ActiveDims::with_projection(RBF, p) * ActiveDims::new(Periodic, vec![2, 0]);
+        let params = [1.0, 0.5, 0.4, 1.1];
+
+        assert_gradients(&kernel, &params, 3);
+    }
+}
@@ -3,12 +3,12 @@ use crate::ParamsDifferentiableKernel;
 use crate::Value;
 use crate::ValueDifferentiableKernel;
 use crate::{KernelMul, PositiveDefiniteKernel};
-use opensrdk_linear_algebra::Vector;
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Display, Formatter};
 use std::marker::PhantomData;
 use std::{ops::Add, ops::Mul};
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KernelAdd<L, R, T>
 where
     L: PositiveDefiniteKernel<T>,
@@ -33,6 +33,30 @@ where
             phantom: PhantomData,
         }
     }
+
+    fn split_params<'a>(&self, params: &'a [f64]) -> Result<(&'a [f64], &'a [f64]), KernelError> {
+        if params.len() != self.params_len() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+
+        Ok(params.split_at(self.lhs.params_len()))
+    }
+}
+
+/// Writes `lhs + rhs`, or `(lhs + rhs)` with the alternate flag `{:#}` which `KernelMul` uses for its operands.
+impl<L, R, T> Display for KernelAdd<L, R, T>
+where
+    L: PositiveDefiniteKernel<T> + Display,
+    R: PositiveDefiniteKernel<T> + Display,
+    T: Value,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "({} + {})", self.lhs, self.rhs)
+        } else {
+            write!(f, "{} + {}", self.lhs, self.rhs)
+        }
+    }
 }
 
 impl<L, R, T> PositiveDefiniteKernel<T> for KernelAdd<L, R, T>
@@ -45,10 +69,24 @@ where
         self.lhs.params_len() + self.rhs.params_len()
     }
 
+    fn params_names(&self) -> Vec<String> {
+        self.lhs
+            .params_names()
+            .into_iter()
+            .map(|name| format!("add.lhs.{}", name))
+            .chain(
+                self.rhs
+                    .params_names()
+                    .into_iter()
+                    .map(|name| format!("add.rhs.{}", name)),
+            )
+            .collect()
+    }
+
     fn value(&self, params: &[f64], x: &T, xprime: &T) -> Result<f64, KernelError> {
-        let lhs_params_len = self.lhs.params_len();
-        let fx = self.lhs.value(&params[..lhs_params_len], x, xprime)?;
-        let gx = self.rhs.value(&params[lhs_params_len..], x, xprime)?;
+        let (lhs_params, rhs_params) = self.split_params(params)?;
+        let fx = self.lhs.value(lhs_params, x, xprime)?;
+        let gx = self.rhs.value(rhs_params, x, xprime)?;
 
         let hx = fx + gx;
 
@@ -91,25 +129,24 @@ where
     T: Value,
 {
     fn ln_diff_value(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError> {
-        let diff_rhs = &self
-            .rhs
-            .ln_diff_value(params, x, xprime)
-            .unwrap()
-            .clone()
-            .col_mat();
-        let diff_lhs = &self
+        let (lhs_params, rhs_params) = self.split_params(params)?;
+        let fx = self.lhs.value(lhs_params, x, xprime)?;
+        let gx = self.rhs.value(rhs_params, x, xprime)?;
+        let hx = fx + gx;
+        let diff = self
             .lhs
-            .ln_diff_value(params, x, xprime)
-            .unwrap()
-            .clone()
-            .col_mat();
-        let value_rhs = vec![self.rhs.value(params, x, xprime).unwrap()].col_mat();
-        let value_lhs = vec![self.lhs.value(params, x, xprime).unwrap()].col_mat();
-        let diff = ((&value_rhs * diff_rhs + &value_lhs * diff_lhs)
-            * (&value_rhs + value_lhs)[(0, 0)].powi(-1))
-        .vec();
+            .ln_diff_value(lhs_params, x, xprime)?
+            .iter()
+            .zip(self.rhs.ln_diff_value(rhs_params, x, xprime)?.iter())
+            .map(|(diff_fx, diff_gx)| (fx * diff_fx + gx * diff_gx) / hx)
+            .collect();
+
         Ok(diff)
     }
+
+    fn is_value_differentiable(&self) -> bool {
+        self.lhs.is_value_differentiable() && self.rhs.is_value_differentiable()
+    }
 }
 
 impl<L, R, T> ParamsDifferentiableKernel<T> for KernelAdd<L, R, T>
@@ -119,23 +156,70 @@ where
     T: Value,
 {
     fn ln_diff_params(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError> {
-        let diff_rhs = &self
-            .rhs
-            .ln_diff_params(params, x, xprime)
-            .unwrap()
-            .clone()
-            .col_mat();
-        let diff_lhs = &self
+        let (lhs_params, rhs_params) = self.split_params(params)?;
+        let fx = self.lhs.value(lhs_params, x, xprime)?;
+        let gx = self.rhs.value(rhs_params, x, xprime)?;
+        let hx = fx + gx;
+        let diff = self
             .lhs
-            .ln_diff_params(params, x, xprime)
-            .unwrap()
-            .clone()
-            .col_mat();
-        let value_rhs = vec![self.rhs.value(params, x, xprime).unwrap()].col_mat();
-        let value_lhs = vec![self.lhs.value(params, x, xprime).unwrap()].col_mat();
-        let diff = ((&value_rhs * diff_rhs + &value_lhs * diff_lhs)
-            * (&value_rhs + value_lhs)[(0, 0)].powi(-1))
-        .vec();
+            .ln_diff_params(lhs_params, x, xprime)?
+            .iter()
+            .map(|diff_fx| fx / hx * diff_fx)
+            .chain(
+                self.rhs
+                    .ln_diff_params(rhs_params, x, xprime)?
+                    .iter()
+                    .map(|diff_gx| gx / hx * diff_gx),
+            )
+            .collect();
+
         Ok(diff)
     }
+
+    fn is_params_differentiable(&self) -> bool {
+        self.lhs.is_params_differentiable() && self.rhs.is_params_differentiable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let kernel = RBF + Constant * Periodic;
+        let params = [1.5, 2.0, 0.7, 0.3, 1.2];
+        let x = vec![0.1, 0.5, -0.3];
+        let xprime = vec![0.4, -0.2, 0.8];
+
+        let check = check_params_gradient(&kernel, &params, &x, &xprime).unwrap();
+
+        assert_eq!(check.analytic.len(), kernel.params_len());
+        assert!(check.max_error() < 1e-6, "{:?}", check);
+    }
+
+    #[test]
+    fn it_works2() {
+        let kernel = (RBF + Constant * Periodic) + Constant * (RBF + ARD(3));
+        let params = [1.5, 2.0, 0.7, 0.3, 1.2, 0.4, 0.9, 3.0, 0.2, 0.5, 1.0];
+        let x = vec![0.1, 0.5, -0.3];
+        let xprime = vec![0.4, -0.2, 0.8];
+
+        let params_check = check_params_gradient(&kernel, &params, &x, &xprime).unwrap();
+        let value_check = check_value_gradient(&kernel, &params, &x, &xprime).unwrap();
+
+        assert_eq!(params_check.analytic.len(), kernel.params_len());
+        assert!(params_check.max_error() < 1e-6, "{:?}", params_check);
+        assert!(value_check.max_error() < 1e-6, "{:?}", value_check);
+    }
+
+    #[test]
+    fn it_works3() {
+        let kernel = RBF + Constant * Periodic;
+
+        match kernel.ln_diff_params(&[1.0, 1.0], &vec![0.0], &vec![0.0]) {
+            Err(KernelError::ParametersLengthMismatch) => (),
+            _ => panic!(),
+        };
+    }
 }
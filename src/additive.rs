@@ -0,0 +1,318 @@
+use crate::{
+    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, PositiveDefiniteKernel,
+    ValueDifferentiableKernel,
+};
+use std::{ops::Add, ops::Mul};
+
+/// Additive kernel of all interaction orders up to `order`, `Σ_r variance_r e_r(z_1, ..., z_D)`,
+/// where `z_d` is the base kernel on dimension `d` alone and `e_r` the elementary symmetric polynomial of order `r`.
+///
+/// `e_r` is computed by the Newton–Girard formula from the power sums of `z_d`, in `O(D * order)`.
+/// Params are those of the base kernel for each dimension in order, followed by `variance_1, ..., variance_order`.
+/// https://arxiv.org/abs/1112.4394
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Additive<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+{
+    kernel: K,
+    dims: usize,
+    order: usize,
+}
+
+impl<K> Additive<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+{
+    /// `order` needs to be between 1 and `dims`, as higher orders have no terms.
+    pub fn new(kernel: K, dims: usize, order: usize) -> Result<Self, KernelError> {
+        if order == 0 || order > dims {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        Ok(Self {
+            kernel,
+            dims,
+            order,
+        })
+    }
+
+    pub fn kernel_ref(&self) -> &K {
+        &self.kernel
+    }
+
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    fn check(&self, params: &[f64], x: &[f64], xprime: &[f64]) -> Result<(), KernelError> {
+        if params.len() != self.params_len() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+        if x.len() != self.dims || xprime.len() != self.dims {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        Ok(())
+    }
+
+    fn base_params<'a>(&self, params: &'a [f64], d: usize) -> &'a [f64] {
+        let len = self.kernel.params_len();
+
+        &params[d * len..(d + 1) * len]
+    }
+
+    /// `f(d, [x_d], [x'_d])` for each dimension.
+    fn each_dim<R, F>(&self, x: &[f64], xprime: &[f64], f: F) -> Result<Vec<R>, KernelError>
+    where
+        F: Fn(usize, &Vec<f64>, &Vec<f64>) -> Result<R, KernelError>,
+    {
+        let mut xd = vec![0.0];
+        let mut xprimed = vec![0.0];
+
+        (0..self.dims)
+            .map(|d| {
+                xd[0] = x[d];
+                xprimed[0] = xprime[d];
+                f(d, &xd, &xprimed)
+            })
+            .collect()
+    }
+
+    /// `e_0, ..., e_order` of `z`.
+    fn elementary_symmetric(&self, z: &[f64]) -> Vec<f64> {
+        let power_sums = (1..=self.order)
+            .map(|k| z.iter().map(|z_d| z_d.powi(k as i32)).sum::<f64>())
+            .collect::<Vec<f64>>();
+
+        let mut e = vec![1.0];
+        for n in 1..=self.order {
+            let e_n = (1..=n)
+                .map(|k| {
+                    let sign = if k % 2 == 1 { 1.0 } else { -1.0 };
+                    sign * e[n - k] * power_sums[k - 1]
+                })
+                .sum::<f64>()
+                / n as f64;
+            e.push(e_n);
+        }
+
+        e
+    }
+
+    /// `Σ_r variance_r e_r`
+    fn weighted_sum(variances: &[f64], e: &[f64]) -> f64 {
+        variances
+            .iter()
+            .zip(e[1..].iter())
+            .map(|(variance, e_r)| variance * e_r)
+            .sum()
+    }
+
+    /// `∂ value / ∂ z_d` for each dimension, using `e_r` without `z_d`, `e_r^(-d) = e_r - z_d e_(r-1)^(-d)`.
+    fn diff_z(&self, variances: &[f64], z: &[f64], e: &[f64]) -> Vec<f64> {
+        z.iter()
+            .map(|z_d| {
+                let mut e_without = 1.0;
+                let mut diff = 0.0;
+                for r in 1..=self.order {
+                    diff += variances[r - 1] * e_without;
+                    e_without = e[r] - z_d * e_without;
+                }
+
+                diff
+            })
+            .collect()
+    }
+}
+
+impl<K> PositiveDefiniteKernel<Vec<f64>> for Additive<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+{
+    fn params_len(&self) -> usize {
+        self.dims * self.kernel.params_len() + self.order
+    }
+
+    fn params_names(&self) -> Vec<String> {
+        let base = (0..self.dims).flat_map(|d| {
+            self.kernel
+                .params_names()
+                .into_iter()
+                .map(move |name| format!("additive.dim{}.{}", d, name))
+        });
+        let variances = (1..=self.order).map(|r| format!("additive.order{}.variance", r));
+
+        base.chain(variances).collect()
+    }
+
+    fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
+        self.check(params, x, xprime)?;
+
+        let z = self.each_dim(x, xprime, |d, xd, xprimed| {
+            self.kernel.value(self.base_params(params, d), xd, xprimed)
+        })?;
+        let e = self.elementary_symmetric(&z);
+        let variances = &params[self.dims * self.kernel.params_len()..];
+
+        let fx = Self::weighted_sum(variances, &e);
+
+        Ok(fx)
+    }
+}
+
+impl<K, R> Add<R> for Additive<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelAdd<Self, R, Vec<f64>>;
+
+    fn add(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<K, R> Mul<R> for Additive<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelMul<Self, R, Vec<f64>>;
+
+    fn mul(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<K> ValueDifferentiableKernel<Vec<f64>> for Additive<K>
+where
+    K: ValueDifferentiableKernel<Vec<f64>>,
+{
+    fn ln_diff_value(
+        &self,
+        params: &[f64],
+        x: &Vec<f64>,
+        xprime: &Vec<f64>,
+    ) -> Result<Vec<f64>, KernelError> {
+        self.check(params, x, xprime)?;
+
+        let z_and_diff = self.each_dim(x, xprime, |d, xd, xprimed| {
+            let base_params = self.base_params(params, d);
+            let z_d = self.kernel.value(base_params, xd, xprimed)?;
+            let diff = self.kernel.ln_diff_value(base_params, xd, xprimed)?;
+
+            Ok((z_d, z_d * diff[0]))
+        })?;
+        let z = z_and_diff.iter().map(|&(z_d, _)| z_d).collect::<Vec<f64>>();
+        let e = self.elementary_symmetric(&z);
+        let variances = &params[self.dims * self.kernel.params_len()..];
+        let fx = Self::weighted_sum(variances, &e);
+
+        let diff = self
+            .diff_z(variances, &z, &e)
+            .iter()
+            .zip(z_and_diff.iter())
+            .map(|(diff_z, &(_, diff_x))| diff_z * diff_x / fx)
+            .collect();
+
+        Ok(diff)
+    }
+
+    fn is_value_differentiable(&self) -> bool {
+        self.kernel.is_value_differentiable()
+    }
+}
+
+impl<K> ParamsDifferentiableKernel<Vec<f64>> for Additive<K>
+where
+    K: ParamsDifferentiableKernel<Vec<f64>>,
+{
+    fn ln_diff_params(
+        &self,
+        params: &[f64],
+        x: &Vec<f64>,
+        xprime: &Vec<f64>,
+    ) -> Result<Vec<f64>, KernelError> {
+        self.check(params, x, xprime)?;
+
+        let z_and_diff = self.each_dim(x, xprime, |d, xd, xprimed| {
+            let base_params = self.base_params(params, d);
+            let z_d = self.kernel.value(base_params, xd, xprimed)?;
+            let diff = self.kernel.ln_diff_params(base_params, xd, xprimed)?;
+
+            Ok((z_d, diff))
+        })?;
+        let z = z_and_diff.iter().map(|(z_d, _)| *z_d).collect::<Vec<f64>>();
+        let e = self.elementary_symmetric(&z);
+        let variances = &params[self.dims * self.kernel.params_len()..];
+        let fx = Self::weighted_sum(variances, &e);
+
+        let diff_base = self
+            .diff_z(variances, &z, &e)
+            .iter()
+            .zip(z_and_diff.iter())
+            .flat_map(|(diff_z, (z_d, diff))| diff.iter().map(move |d| diff_z * z_d * d / fx))
+            .collect::<Vec<f64>>();
+        let diff_variances = e[1..].iter().map(|e_r| e_r / fx).collect::<Vec<f64>>();
+
+        Ok([diff_base, diff_variances].concat())
+    }
+
+    fn is_params_differentiable(&self) -> bool {
+        self.kernel.is_params_differentiable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let kernel = Additive::new(RBF, 3, 3).unwrap();
+        let params = [1.0, 0.5, 1.5, 0.8, 0.7, 1.2, 0.3, 0.6, 0.9];
+        let x = vec![0.1, 0.5, -0.3];
+        let xprime = vec![0.4, -0.2, 0.8];
+
+        let z = (0..3)
+            .map(|d| {
+                RBF.value(&params[2 * d..2 * d + 2], &vec![x[d]], &vec![xprime[d]])
+                    .unwrap()
+            })
+            .collect::<Vec<f64>>();
+        let expected = 0.3 * (z[0] + z[1] + z[2])
+            + 0.6 * (z[0] * z[1] + z[0] * z[2] + z[1] * z[2])
+            + 0.9 * z[0] * z[1] * z[2];
+
+        let test_value = kernel.value(&params, &x, &xprime).unwrap();
+
+        assert!((test_value - expected).abs() < 1e-12);
+        assert_eq!(kernel.params_names()[2], "additive.dim1.rbf.variance");
+        match kernel.value(&params, &vec![0.0; 2], &vec![0.0; 2]) {
+            Err(KernelError::InvalidArgument) => (),
+            _ => panic!(),
+        };
+        for order in [0, 4] {
+            match Additive::new(RBF, 3, order) {
+                Err(KernelError::InvalidArgument) => (),
+                _ => panic!(),
+            };
+        }
+    }
+
+    #[test]
+    fn check_gradients() {
+        let kernel = Additive::new(RBF + Periodic, 4, 3).unwrap();
+        let params = (0..kernel.params_len())
+            .map(|i| 0.5 + 0.05 * i as f64)
+            .collect::<Vec<f64>>();
+
+        assert_gradients(&kernel, &params, 4);
+    }
+}
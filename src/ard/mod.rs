@@ -3,6 +3,7 @@ use crate::{
     KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, ValueDifferentiableKernel,
 };
 use rayon::prelude::*;
+use std::fmt::{self, Display, Formatter};
 use std::{ops::Add, ops::Mul};
 
 fn weighted_norm_pow(params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> f64 {
@@ -15,22 +16,35 @@ fn weighted_norm_pow(params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> f64 {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ARD(pub usize);
 
+impl Display for ARD {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "ARD({})", self.0)
+    }
+}
+
 impl PositiveDefiniteKernel<Vec<f64>> for ARD {
     fn params_len(&self) -> usize {
         self.0
     }
 
+    fn params_names(&self) -> Vec<String> {
+        (0..self.0)
+            .map(|d| format!("ard.relevance.{}", d))
+            .collect()
+    }
+
     fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
         if params.len() != self.0 {
-            return Err(KernelError::ParametersLengthMismatch.into());
+            return Err(KernelError::ParametersLengthMismatch);
         }
         if x.len() != self.0 || xprime.len() != self.0 {
-            return Err(KernelError::InvalidArgument.into());
+            return Err(KernelError::InvalidArgument);
         }
 
-        let fx = (-weighted_norm_pow(&params, x, xprime)).exp();
+        let fx = (-weighted_norm_pow(params, x, xprime)).exp();
 
         Ok(fx)
     }
@@ -105,4 +119,9 @@ mod tests {
 
         assert_eq!(test_value, (-1f64).exp());
     }
+
+    #[test]
+    fn check_gradients() {
+        assert_gradients(&ARD(3), &[1.5, 0.8, 0.6], 3);
+    }
 }
@@ -0,0 +1,325 @@
+use crate::{
+    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, PositiveDefiniteKernel, Value,
+    ValueDifferentiableKernel,
+};
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::{ops::Add, ops::Mul};
+
+/// Object-safe counterpart of `PositiveDefiniteKernel` and the differentiable kernel traits.
+///
+/// The gradient methods return `KernelError::NotDifferentiable` unless the implementor provides them.
+pub trait DynPositiveDefiniteKernel<T>: Debug + Send + Sync
+where
+    T: Value,
+{
+    fn params_len(&self) -> usize;
+
+    fn params_names(&self) -> Vec<String>;
+
+    fn value(&self, params: &[f64], x: &T, xprime: &T) -> Result<f64, KernelError>;
+
+    fn is_params_differentiable(&self) -> bool {
+        false
+    }
+
+    fn is_value_differentiable(&self) -> bool {
+        false
+    }
+
+    fn ln_diff_params(
+        &self,
+        _params: &[f64],
+        _x: &T,
+        _xprime: &T,
+    ) -> Result<Vec<f64>, KernelError> {
+        Err(KernelError::NotDifferentiable)
+    }
+
+    fn ln_diff_value(&self, _params: &[f64], _x: &T, _xprime: &T) -> Result<Vec<f64>, KernelError> {
+        Err(KernelError::NotDifferentiable)
+    }
+}
+
+#[derive(Debug)]
+struct Plain<K>(K);
+#[derive(Debug)]
+struct ParamsDifferentiable<K>(K);
+#[derive(Debug)]
+struct ValueDifferentiable<K>(K);
+#[derive(Debug)]
+struct Differentiable<K>(K);
+
+macro_rules! impl_dyn_kernel {
+    ($wrapper:ident, $bound:path, { $($methods:item)* }) => {
+        impl<T, K> DynPositiveDefiniteKernel<T> for $wrapper<K>
+        where
+            T: Value,
+            K: $bound,
+        {
+            fn params_len(&self) -> usize {
+                self.0.params_len()
+            }
+
+            fn params_names(&self) -> Vec<String> {
+                self.0.params_names()
+            }
+
+            fn value(&self, params: &[f64], x: &T, xprime: &T) -> Result<f64, KernelError> {
+                self.0.value(params, x, xprime)
+            }
+
+            $($methods)*
+        }
+    };
+}
+
+impl_dyn_kernel!(Plain, PositiveDefiniteKernel<T>, {});
+
+impl_dyn_kernel!(ParamsDifferentiable, ParamsDifferentiableKernel<T>, {
+    fn is_params_differentiable(&self) -> bool {
+        self.0.is_params_differentiable()
+    }
+
+    fn ln_diff_params(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError> {
+        self.0.ln_diff_params(params, x, xprime)
+    }
+});
+
+impl_dyn_kernel!(ValueDifferentiable, ValueDifferentiableKernel<T>, {
+    fn is_value_differentiable(&self) -> bool {
+        self.0.is_value_differentiable()
+    }
+
+    fn ln_diff_value(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError> {
+        self.0.ln_diff_value(params, x, xprime)
+    }
+});
+
+impl_dyn_kernel!(Differentiable, DifferentiableKernel<T>, {
+    fn is_params_differentiable(&self) -> bool {
+        self.0.is_params_differentiable()
+    }
+
+    fn is_value_differentiable(&self) -> bool {
+        self.0.is_value_differentiable()
+    }
+
+    fn ln_diff_params(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError> {
+        self.0.ln_diff_params(params, x, xprime)
+    }
+
+    fn ln_diff_value(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError> {
+        self.0.ln_diff_value(params, x, xprime)
+    }
+});
+
+/// `ParamsDifferentiableKernel + ValueDifferentiableKernel`, to name both in a single bound.
+pub trait DifferentiableKernel<T>:
+    ParamsDifferentiableKernel<T> + ValueDifferentiableKernel<T>
+where
+    T: Value,
+{
+}
+
+impl<T, K> DifferentiableKernel<T> for K
+where
+    T: Value,
+    K: ParamsDifferentiableKernel<T> + ValueDifferentiableKernel<T>,
+{
+}
+
+/// Type-erased kernel, so that kernels can be composed at runtime or kept in a `Vec`.
+///
+/// Cloning only clones the `Arc`. Each constructor keeps the gradients the given kernel provides;
+/// the others return `KernelError::NotDifferentiable`. `is_params_differentiable` and
+/// `is_value_differentiable` look through composites, so a sum with a plain boxed kernel reports `false`.
+#[derive(Clone, Debug)]
+pub struct BoxedKernel<T>
+where
+    T: Value,
+{
+    kernel: Arc<dyn DynPositiveDefiniteKernel<T>>,
+}
+
+impl<T> BoxedKernel<T>
+where
+    T: Value,
+{
+    pub fn new<K>(kernel: K) -> Self
+    where
+        K: PositiveDefiniteKernel<T> + 'static,
+    {
+        Self {
+            kernel: Arc::new(Plain(kernel)),
+        }
+    }
+
+    pub fn params_differentiable<K>(kernel: K) -> Self
+    where
+        K: ParamsDifferentiableKernel<T> + 'static,
+    {
+        Self {
+            kernel: Arc::new(ParamsDifferentiable(kernel)),
+        }
+    }
+
+    pub fn value_differentiable<K>(kernel: K) -> Self
+    where
+        K: ValueDifferentiableKernel<T> + 'static,
+    {
+        Self {
+            kernel: Arc::new(ValueDifferentiable(kernel)),
+        }
+    }
+
+    pub fn differentiable<K>(kernel: K) -> Self
+    where
+        K: DifferentiableKernel<T> + 'static,
+    {
+        Self {
+            kernel: Arc::new(Differentiable(kernel)),
+        }
+    }
+
+    pub fn from_arc(kernel: Arc<dyn DynPositiveDefiniteKernel<T>>) -> Self {
+        Self { kernel }
+    }
+}
+
+impl<T> PositiveDefiniteKernel<T> for BoxedKernel<T>
+where
+    T: Value,
+{
+    fn params_len(&self) -> usize {
+        self.kernel.params_len()
+    }
+
+    fn params_names(&self) -> Vec<String> {
+        self.kernel.params_names()
+    }
+
+    fn value(&self, params: &[f64], x: &T, xprime: &T) -> Result<f64, KernelError> {
+        self.kernel.value(params, x, xprime)
+    }
+}
+
+impl<T, R> Add<R> for BoxedKernel<T>
+where
+    T: Value,
+    R: PositiveDefiniteKernel<T>,
+{
+    type Output = KernelAdd<Self, R, T>;
+
+    fn add(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<T, R> Mul<R> for BoxedKernel<T>
+where
+    T: Value,
+    R: PositiveDefiniteKernel<T>,
+{
+    type Output = KernelMul<Self, R, T>;
+
+    fn mul(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<T> ValueDifferentiableKernel<T> for BoxedKernel<T>
+where
+    T: Value,
+{
+    fn ln_diff_value(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError> {
+        self.kernel.ln_diff_value(params, x, xprime)
+    }
+
+    fn is_value_differentiable(&self) -> bool {
+        self.kernel.is_value_differentiable()
+    }
+}
+
+impl<T> ParamsDifferentiableKernel<T> for BoxedKernel<T>
+where
+    T: Value,
+{
+    fn ln_diff_params(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError> {
+        self.kernel.ln_diff_params(params, x, xprime)
+    }
+
+    fn is_params_differentiable(&self) -> bool {
+        self.kernel.is_params_differentiable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let mut kernels = vec![
+            BoxedKernel::differentiable(RBF),
+            BoxedKernel::differentiable(Periodic + Constant),
+            BoxedKernel::new(Convolutional::new(Linear)),
+        ];
+        kernels.push(BoxedKernel::differentiable(
+            "Periodic".parse::<KernelExpression>().unwrap(),
+        ));
+        let x = vec![0.1, 0.5];
+        let xprime = vec![0.4, -0.2];
+
+        let sum = kernels
+            .iter()
+            .cloned()
+            .fold(BoxedKernel::differentiable(Constant), |sum, kernel| {
+                BoxedKernel::new(sum + kernel)
+            });
+        let params = [0.5, 1.0, 2.0, 0.3, 1.2, 1.5, 0.8, 0.9];
+
+        let expected =
+            (Constant + RBF + (Periodic + Constant) + Convolutional::new(Linear) + Periodic)
+                .value(&params, &x, &xprime)
+                .unwrap();
+
+        assert_eq!(sum.params_len(), 8);
+        assert_eq!(sum.value(&params, &x, &xprime).unwrap(), expected);
+        match kernels[2].ln_diff_params(&[], &x, &xprime) {
+            Err(KernelError::NotDifferentiable) => (),
+            _ => panic!(),
+        };
+        assert!(!kernels[2].is_value_differentiable());
+    }
+
+    #[test]
+    fn check_gradients() {
+        let lhs = BoxedKernel::differentiable(RBF + Periodic);
+        let kernel = BoxedKernel::differentiable(lhs * BoxedKernel::differentiable(ARD(2)));
+        let params = [1.0, 0.5, 0.4, 1.1, 0.7, 1.3];
+
+        assert!(kernel.is_params_differentiable());
+        assert_gradients(&kernel, &params, 2);
+    }
+
+    #[test]
+    fn it_works2() {
+        let kernel = BoxedKernel::differentiable(RBF + BoxedKernel::new(Periodic));
+        let x = vec![0.1, 0.5];
+
+        assert!(!kernel.is_params_differentiable());
+        assert!(!kernel.is_value_differentiable());
+        match kernel.ln_diff_params(&[1.0, 0.5, 0.4, 1.1], &x, &x) {
+            Err(KernelError::NotDifferentiable) => (),
+            _ => panic!(),
+        };
+        let transformed = Transformed::new(
+            kernel * BoxedKernel::params_differentiable(Constant),
+            vec![ParamsTransform::Exp; 5],
+        )
+        .unwrap();
+        assert!(!BoxedKernel::differentiable(transformed).is_params_differentiable());
+        assert!(BoxedKernel::value_differentiable(RBF + ARD(2)).is_value_differentiable());
+    }
+}
@@ -2,13 +2,21 @@ use super::PositiveDefiniteKernel;
 use crate::{KernelAdd, KernelError, KernelMul};
 use crate::{ParamsDifferentiableKernel, Value, ValueDifferentiableKernel};
 use std::fmt::Debug;
+use std::fmt::{self, Display, Formatter};
 use std::{ops::Add, ops::Mul};
 
 const PARAMS_LEN: usize = 1;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Constant;
 
+impl Display for Constant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Constant")
+    }
+}
+
 impl<T> PositiveDefiniteKernel<T> for Constant
 where
     T: Value,
@@ -17,9 +25,13 @@ where
         PARAMS_LEN
     }
 
+    fn params_names(&self) -> Vec<String> {
+        vec!["constant.value".to_owned()]
+    }
+
     fn value(&self, params: &[f64], _: &T, _: &T) -> Result<f64, KernelError> {
         if params.len() != PARAMS_LEN {
-            return Err(KernelError::ParametersLengthMismatch.into());
+            return Err(KernelError::ParametersLengthMismatch);
         }
 
         let fx = params[0];
@@ -62,14 +74,16 @@ impl ValueDifferentiableKernel<Vec<f64>> for Constant {
     }
 }
 
-impl ParamsDifferentiableKernel<Vec<f64>> for Constant {
-    fn ln_diff_params(
-        &self,
-        _params: &[f64],
-        _x: &Vec<f64>,
-        _xprime: &Vec<f64>,
-    ) -> Result<Vec<f64>, KernelError> {
-        let diff = vec![1.0];
+impl<T> ParamsDifferentiableKernel<T> for Constant
+where
+    T: Value,
+{
+    fn ln_diff_params(&self, params: &[f64], _x: &T, _xprime: &T) -> Result<Vec<f64>, KernelError> {
+        if params.len() != PARAMS_LEN {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+
+        let diff = vec![1.0 / params[0]];
         Ok(diff)
     }
 }
@@ -87,4 +101,9 @@ mod tests {
 
         assert_eq!(test_value, 1.0);
     }
+
+    #[test]
+    fn check_gradients() {
+        assert_gradients(&Constant, &[1.5], 3);
+    }
 }
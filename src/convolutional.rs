@@ -1,5 +1,5 @@
 use crate::Value;
-use crate::{KernelError, PositiveDefiniteKernel};
+use crate::{KernelError, ParamsDifferentiableKernel, PositiveDefiniteKernel};
 use rayon::prelude::*;
 use std::fmt::Debug;
 
@@ -23,7 +23,28 @@ impl Convolutable for Vec<f64> {
     }
 }
 
+/// Sequence of points compared part by part, for tests.
+#[cfg(test)]
+#[derive(Clone, Debug)]
+pub(crate) struct Sequence(pub Vec<Vec<f64>>);
+
+#[cfg(test)]
+impl Convolutable for Sequence {
+    fn parts_len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn part(&self, index: usize) -> &Vec<f64> {
+        &self.0[index]
+    }
+
+    fn data_len(&self) -> usize {
+        self.0.iter().map(|part| part.len()).sum()
+    }
+}
+
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Convolutional<K>
 where
     K: PositiveDefiniteKernel<Vec<f64>>,
@@ -53,13 +74,21 @@ where
         self.kernel.params_len()
     }
 
+    fn params_names(&self) -> Vec<String> {
+        self.kernel
+            .params_names()
+            .into_iter()
+            .map(|name| format!("convolutional.{}", name))
+            .collect()
+    }
+
     fn value(&self, params: &[f64], x: &T, xprime: &T) -> Result<f64, KernelError> {
         if params.len() != self.kernel.params_len() {
-            return Err(KernelError::ParametersLengthMismatch.into());
+            return Err(KernelError::ParametersLengthMismatch);
         }
         let p = x.parts_len();
         if p != xprime.parts_len() {
-            return Err(KernelError::InvalidArgument.into());
+            return Err(KernelError::InvalidArgument);
         }
 
         let fx = (0..p)
@@ -71,6 +100,40 @@ where
     }
 }
 
+impl<T, K> ParamsDifferentiableKernel<T> for Convolutional<K>
+where
+    T: Convolutable,
+    K: ParamsDifferentiableKernel<Vec<f64>>,
+{
+    fn ln_diff_params(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError> {
+        let fx = self.value(params, x, xprime)?;
+
+        let diff = (0..x.parts_len())
+            .into_par_iter()
+            .map(|pi| {
+                let k = self.kernel.value(params, x.part(pi), xprime.part(pi))?;
+                let diff = self
+                    .kernel
+                    .ln_diff_params(params, x.part(pi), xprime.part(pi))?;
+                Ok(diff.into_iter().map(|d| k * d).collect::<Vec<f64>>())
+            })
+            .collect::<Result<Vec<Vec<f64>>, KernelError>>()?
+            .into_iter()
+            .fold(vec![0.0; params.len()], |sum, diff| {
+                sum.iter().zip(diff.iter()).map(|(s, d)| s + d).collect()
+            })
+            .into_iter()
+            .map(|d| d / fx)
+            .collect();
+
+        Ok(diff)
+    }
+
+    fn is_params_differentiable(&self) -> bool {
+        self.kernel.is_params_differentiable()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
@@ -85,4 +148,16 @@ mod tests {
             _ => panic!(),
         };
     }
+
+    #[test]
+    fn check_gradients() {
+        let kernel = Convolutional::new(RBF + Periodic);
+        let params = [1.0, 0.5, 0.4, 1.1];
+        let x = vec![0.1, 0.5, -0.3];
+        let xprime = vec![0.4, -0.2, 0.8];
+
+        let params_check = check_params_gradient(&kernel, &params, &x, &xprime).unwrap();
+
+        assert!(params_check.max_error() < 1e-6, "{:?}", params_check);
+    }
 }
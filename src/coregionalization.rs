@@ -0,0 +1,501 @@
+use crate::{
+    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, PositiveDefiniteKernel,
+    ValueDifferentiableKernel,
+};
+use opensrdk_linear_algebra::Matrix;
+use std::{ops::Add, ops::Mul};
+
+/// Params of the inner kernel, `W` and `kappa`.
+type SplitParams<'a> = (&'a [f64], &'a [f64], &'a [f64]);
+
+/// Intrinsic coregionalization model `B[i][j] * k(x, x')` on inputs `(x, task index)`.
+///
+/// The task covariance is `B = W W^T + diag(kappa)` with `W` of `tasks` rows and `rank` columns.
+/// Params are those of the inner kernel followed by `W` in row-major order and `kappa`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Coregionalization<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+{
+    kernel: K,
+    tasks: usize,
+    rank: usize,
+}
+
+impl<K> Coregionalization<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+{
+    pub fn new(kernel: K, tasks: usize, rank: usize) -> Self {
+        Self {
+            kernel,
+            tasks,
+            rank,
+        }
+    }
+
+    pub fn kernel_ref(&self) -> &K {
+        &self.kernel
+    }
+
+    pub fn tasks(&self) -> usize {
+        self.tasks
+    }
+
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+
+    fn split_params<'a>(&self, params: &'a [f64]) -> Result<SplitParams<'a>, KernelError> {
+        if params.len() != self.params_len() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+
+        let (kernel_params, params) = params.split_at(self.kernel.params_len());
+        let (w, kappa) = params.split_at(self.tasks * self.rank);
+
+        Ok((kernel_params, w, kappa))
+    }
+
+    fn b(&self, w: &[f64], kappa: &[f64], i: usize, j: usize) -> f64 {
+        let wwt = (0..self.rank)
+            .map(|r| w[i * self.rank + r] * w[j * self.rank + r])
+            .sum::<f64>();
+
+        if i == j {
+            wwt + kappa[i]
+        } else {
+            wwt
+        }
+    }
+
+    /// Task covariance matrix `B`.
+    pub fn task_covariance(&self, params: &[f64]) -> Result<Matrix, KernelError> {
+        let (_, w, kappa) = self.split_params(params)?;
+
+        let mut b = Matrix::new(self.tasks, self.tasks);
+        for j in 0..self.tasks {
+            for i in 0..self.tasks {
+                b[(i, j)] = self.b(w, kappa, i, j);
+            }
+        }
+
+        Ok(b)
+    }
+
+    /// Gram matrix `B ⊗ K(X, X)` for all tasks observed at the same inputs `x`.
+    /// Row `t * x.len() + i` is task `t` at `x[i]`.
+    pub fn block_gram_matrix(&self, params: &[f64], x: &[Vec<f64>]) -> Result<Matrix, KernelError> {
+        let (kernel_params, _, _) = self.split_params(params)?;
+        let b = self.task_covariance(params)?;
+        let k = self.kernel.gram_matrix(kernel_params, x)?;
+
+        let n = x.len();
+        let mut block = Matrix::new(self.tasks * n, self.tasks * n);
+        for s in 0..self.tasks {
+            for t in 0..self.tasks {
+                for j in 0..n {
+                    for i in 0..n {
+                        block[(t * n + i, s * n + j)] = b[(t, s)] * k[(i, j)];
+                    }
+                }
+            }
+        }
+
+        Ok(block)
+    }
+}
+
+impl<K> PositiveDefiniteKernel<(Vec<f64>, usize)> for Coregionalization<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+{
+    fn params_len(&self) -> usize {
+        self.kernel.params_len() + self.tasks * self.rank + self.tasks
+    }
+
+    fn params_names(&self) -> Vec<String> {
+        let kernel = self
+            .kernel
+            .params_names()
+            .into_iter()
+            .map(|name| format!("coregionalization.{}", name));
+        let w = (0..self.tasks)
+            .flat_map(|t| (0..self.rank).map(move |r| format!("coregionalization.w.{}.{}", t, r)));
+        let kappa = (0..self.tasks).map(|t| format!("coregionalization.kappa.{}", t));
+
+        kernel.chain(w).chain(kappa).collect()
+    }
+
+    fn value(
+        &self,
+        params: &[f64],
+        x: &(Vec<f64>, usize),
+        xprime: &(Vec<f64>, usize),
+    ) -> Result<f64, KernelError> {
+        let (kernel_params, w, kappa) = self.split_params(params)?;
+        if x.1 >= self.tasks || xprime.1 >= self.tasks {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let fx =
+            self.b(w, kappa, x.1, xprime.1) * self.kernel.value(kernel_params, &x.0, &xprime.0)?;
+
+        Ok(fx)
+    }
+}
+
+impl<K, R> Add<R> for Coregionalization<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+    R: PositiveDefiniteKernel<(Vec<f64>, usize)>,
+{
+    type Output = KernelAdd<Self, R, (Vec<f64>, usize)>;
+
+    fn add(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<K, R> Mul<R> for Coregionalization<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+    R: PositiveDefiniteKernel<(Vec<f64>, usize)>,
+{
+    type Output = KernelMul<Self, R, (Vec<f64>, usize)>;
+
+    fn mul(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<K> ValueDifferentiableKernel<(Vec<f64>, usize)> for Coregionalization<K>
+where
+    K: ValueDifferentiableKernel<Vec<f64>>,
+{
+    fn ln_diff_value(
+        &self,
+        params: &[f64],
+        x: &(Vec<f64>, usize),
+        xprime: &(Vec<f64>, usize),
+    ) -> Result<Vec<f64>, KernelError> {
+        let (kernel_params, _, _) = self.split_params(params)?;
+        if x.1 >= self.tasks || xprime.1 >= self.tasks {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        self.kernel.ln_diff_value(kernel_params, &x.0, &xprime.0)
+    }
+
+    fn is_value_differentiable(&self) -> bool {
+        self.kernel.is_value_differentiable()
+    }
+}
+
+impl<K> ParamsDifferentiableKernel<(Vec<f64>, usize)> for Coregionalization<K>
+where
+    K: ParamsDifferentiableKernel<Vec<f64>>,
+{
+    fn ln_diff_params(
+        &self,
+        params: &[f64],
+        x: &(Vec<f64>, usize),
+        xprime: &(Vec<f64>, usize),
+    ) -> Result<Vec<f64>, KernelError> {
+        let (kernel_params, w, kappa) = self.split_params(params)?;
+        let (i, j) = (x.1, xprime.1);
+        if i >= self.tasks || j >= self.tasks {
+            return Err(KernelError::InvalidArgument);
+        }
+        let b = self.b(w, kappa, i, j);
+
+        let mut diff_w = vec![0.0; w.len()];
+        for r in 0..self.rank {
+            diff_w[i * self.rank + r] += w[j * self.rank + r] / b;
+            diff_w[j * self.rank + r] += w[i * self.rank + r] / b;
+        }
+        let mut diff_kappa = vec![0.0; kappa.len()];
+        if i == j {
+            diff_kappa[i] = 1.0 / b;
+        }
+
+        let diff = [
+            self.kernel.ln_diff_params(kernel_params, &x.0, &xprime.0)?,
+            diff_w,
+            diff_kappa,
+        ]
+        .concat();
+
+        Ok(diff)
+    }
+
+    fn is_params_differentiable(&self) -> bool {
+        self.kernel.is_params_differentiable()
+    }
+}
+
+/// Linear model of coregionalization, the sum of several `Coregionalization` components.
+///
+/// Params are those of each component in order. Components with different kernel types can be
+/// combined through `BoxedKernel`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearModelOfCoregionalization<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+{
+    components: Vec<Coregionalization<K>>,
+}
+
+impl<K> LinearModelOfCoregionalization<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+{
+    pub fn new(components: Vec<Coregionalization<K>>) -> Self {
+        Self { components }
+    }
+
+    pub fn components(&self) -> &[Coregionalization<K>] {
+        &self.components
+    }
+
+    fn split_params<'a>(&self, params: &'a [f64]) -> Result<Vec<&'a [f64]>, KernelError> {
+        if params.len() != self.params_len() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+
+        let mut rest = params;
+        let split = self
+            .components
+            .iter()
+            .map(|component| {
+                let (head, tail) = rest.split_at(component.params_len());
+                rest = tail;
+                head
+            })
+            .collect();
+
+        Ok(split)
+    }
+
+    /// Sum of the task covariance matrices of the components.
+    pub fn task_covariance(&self, params: &[f64]) -> Result<Matrix, KernelError> {
+        self.sum_matrices(params, |component, params| {
+            component.task_covariance(params)
+        })
+    }
+
+    /// Sum of the block Gram matrices of the components, see `Coregionalization::block_gram_matrix`.
+    pub fn block_gram_matrix(&self, params: &[f64], x: &[Vec<f64>]) -> Result<Matrix, KernelError> {
+        self.sum_matrices(params, |component, params| {
+            component.block_gram_matrix(params, x)
+        })
+    }
+
+    fn sum_matrices<F>(&self, params: &[f64], f: F) -> Result<Matrix, KernelError>
+    where
+        F: Fn(&Coregionalization<K>, &[f64]) -> Result<Matrix, KernelError>,
+    {
+        let split = self.split_params(params)?;
+        let mut sum: Option<Matrix> = None;
+
+        for (component, params) in self.components.iter().zip(split) {
+            let matrix = f(component, params)?;
+            sum = match sum {
+                None => Some(matrix),
+                Some(mut sum) => {
+                    if !sum.is_same_size(&matrix) {
+                        return Err(KernelError::InvalidArgument);
+                    }
+                    for (s, m) in sum.elems_mut().iter_mut().zip(matrix.elems().iter()) {
+                        *s += m;
+                    }
+                    Some(sum)
+                }
+            };
+        }
+
+        sum.ok_or(KernelError::InvalidArgument)
+    }
+}
+
+impl<K> PositiveDefiniteKernel<(Vec<f64>, usize)> for LinearModelOfCoregionalization<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+{
+    fn params_len(&self) -> usize {
+        self.components
+            .iter()
+            .map(|component| component.params_len())
+            .sum()
+    }
+
+    fn params_names(&self) -> Vec<String> {
+        self.components
+            .iter()
+            .enumerate()
+            .flat_map(|(q, component)| {
+                component
+                    .params_names()
+                    .into_iter()
+                    .map(move |name| format!("linear_model_of_coregionalization.{}.{}", q, name))
+            })
+            .collect()
+    }
+
+    fn value(
+        &self,
+        params: &[f64],
+        x: &(Vec<f64>, usize),
+        xprime: &(Vec<f64>, usize),
+    ) -> Result<f64, KernelError> {
+        let split = self.split_params(params)?;
+
+        self.components
+            .iter()
+            .zip(split)
+            .map(|(component, params)| component.value(params, x, xprime))
+            .sum()
+    }
+}
+
+impl<K, R> Add<R> for LinearModelOfCoregionalization<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+    R: PositiveDefiniteKernel<(Vec<f64>, usize)>,
+{
+    type Output = KernelAdd<Self, R, (Vec<f64>, usize)>;
+
+    fn add(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<K, R> Mul<R> for LinearModelOfCoregionalization<K>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+    R: PositiveDefiniteKernel<(Vec<f64>, usize)>,
+{
+    type Output = KernelMul<Self, R, (Vec<f64>, usize)>;
+
+    fn mul(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<K> ParamsDifferentiableKernel<(Vec<f64>, usize)> for LinearModelOfCoregionalization<K>
+where
+    K: ParamsDifferentiableKernel<Vec<f64>>,
+{
+    fn ln_diff_params(
+        &self,
+        params: &[f64],
+        x: &(Vec<f64>, usize),
+        xprime: &(Vec<f64>, usize),
+    ) -> Result<Vec<f64>, KernelError> {
+        let split = self.split_params(params)?;
+        let values = self
+            .components
+            .iter()
+            .zip(split.iter())
+            .map(|(component, params)| component.value(params, x, xprime))
+            .collect::<Result<Vec<f64>, KernelError>>()?;
+        let hx = values.iter().sum::<f64>();
+
+        let diff = self
+            .components
+            .iter()
+            .zip(split.iter())
+            .zip(values.iter())
+            .map(|((component, params), fx)| {
+                Ok(component
+                    .ln_diff_params(params, x, xprime)?
+                    .into_iter()
+                    .map(|diff_fx| fx / hx * diff_fx)
+                    .collect::<Vec<f64>>())
+            })
+            .collect::<Result<Vec<Vec<f64>>, KernelError>>()?
+            .concat();
+
+        Ok(diff)
+    }
+
+    fn is_params_differentiable(&self) -> bool {
+        self.components
+            .iter()
+            .all(|component| component.is_params_differentiable())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let kernel = Coregionalization::new(RBF, 2, 1);
+        // W = [1, 2]^T, kappa = [0.5, 0.25]
+        let params = [1.0, 1.0, 1.0, 2.0, 0.5, 0.25];
+        let x = (vec![0.0, 0.0], 0);
+        let xprime = (vec![0.0, 0.0], 1);
+
+        let b = kernel.task_covariance(&params).unwrap();
+
+        assert_eq!(kernel.value(&params, &x, &xprime).unwrap(), 2.0);
+        assert_eq!(kernel.value(&params, &xprime, &xprime).unwrap(), 4.25);
+        assert_eq!(b.elems(), &[1.5, 2.0, 2.0, 4.25]);
+        match kernel.value(&params, &x, &(vec![0.0, 0.0], 2)) {
+            Err(KernelError::InvalidArgument) => (),
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn it_works2() {
+        let kernel = LinearModelOfCoregionalization::new(vec![
+            Coregionalization::new(BoxedKernel::differentiable(RBF), 3, 2),
+            Coregionalization::new(BoxedKernel::differentiable(Periodic), 3, 1),
+        ]);
+        let params = (0..kernel.params_len())
+            .map(|i| 0.3 + 0.1 * i as f64)
+            .collect::<Vec<f64>>();
+        let x = vec![vec![0.1, 0.5], vec![0.4, -0.2]];
+
+        let block = kernel.block_gram_matrix(&params, &x).unwrap();
+        let inputs = (0..3)
+            .flat_map(|t| x.iter().map(move |xi| (xi.clone(), t)))
+            .collect::<Vec<_>>();
+        let gram = kernel.gram_matrix(&params, &inputs).unwrap();
+
+        assert_eq!(block.rows(), 6);
+        for (b, g) in block.elems().iter().zip(gram.elems().iter()) {
+            assert!((b - g).abs() < 1e-12);
+        }
+        assert_eq!(
+            kernel.params_names()[0],
+            "linear_model_of_coregionalization.0.coregionalization.rbf.variance"
+        );
+    }
+
+    #[test]
+    fn check_gradients() {
+        let kernel = LinearModelOfCoregionalization::new(vec![
+            Coregionalization::new(RBF, 3, 2),
+            Coregionalization::new(RBF, 3, 1),
+        ]);
+        let params = [
+            1.0, 0.5, 0.3, -0.2, 0.8, 0.4, 0.1, 0.6, 0.2, 0.3, 0.4, //
+            0.7, 1.2, 0.5, -0.4, 0.9, 0.1, 0.2, 0.3,
+        ];
+
+        for &(i, j) in [(0, 0), (0, 2), (2, 1)].iter() {
+            let x = (vec![0.1, 0.5], i);
+            let xprime = (vec![0.4, -0.2], j);
+
+            let check = check_params_gradient(&kernel, &params, &x, &xprime).unwrap();
+
+            assert!(check.max_error() < 1e-6, "{:?}", check);
+        }
+    }
+}
@@ -1,23 +1,28 @@
 use super::PositiveDefiniteKernel;
+use crate::traits::stationary::{sample_student_t, student_t_density};
 use crate::{
-    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, ValueDifferentiableKernel,
+    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, StationaryKernel,
+    ValueDifferentiableKernel,
 };
 use opensrdk_linear_algebra::Vector;
+use rand::Rng;
 use rayon::prelude::*;
+use std::fmt::{self, Display, Formatter};
 use std::{ops::Add, ops::Mul};
 
 const PARAMS_LEN: usize = 1;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Exponential;
 
 impl Exponential {
     fn norm(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
         if params.len() != PARAMS_LEN {
-            return Err(KernelError::ParametersLengthMismatch.into());
+            return Err(KernelError::ParametersLengthMismatch);
         }
         if x.len() != xprime.len() {
-            return Err(KernelError::InvalidArgument.into());
+            return Err(KernelError::InvalidArgument);
         }
 
         let v = x
@@ -31,11 +36,21 @@ impl Exponential {
     }
 }
 
+impl Display for Exponential {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Exponential")
+    }
+}
+
 impl PositiveDefiniteKernel<Vec<f64>> for Exponential {
     fn params_len(&self) -> usize {
         PARAMS_LEN
     }
 
+    fn params_names(&self) -> Vec<String> {
+        vec!["exponential.lengthscale".to_owned()]
+    }
+
     fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
         let norm = self.norm(params, x, xprime)?;
 
@@ -52,7 +67,13 @@ impl ValueDifferentiableKernel<Vec<f64>> for Exponential {
         x: &Vec<f64>,
         xprime: &Vec<f64>,
     ) -> Result<Vec<f64>, KernelError> {
-        let diff = (-2.0 / params[0] * (x.clone().col_mat() - xprime.clone().col_mat())).vec();
+        let norm = self.norm(params, x, xprime)?;
+        if norm == 0.0 {
+            return Ok(vec![0.0; x.len()]);
+        }
+
+        let diff =
+            (-1.0 / (params[0] * norm) * (x.clone().col_mat() - xprime.clone().col_mat())).vec();
         Ok(diff)
     }
 }
@@ -64,8 +85,10 @@ impl ParamsDifferentiableKernel<Vec<f64>> for Exponential {
         x: &Vec<f64>,
         xprime: &Vec<f64>,
     ) -> Result<Vec<f64>, KernelError> {
-        let diff1 = 2.0 * params[0].powi(-2) * &self.norm(params, x, xprime).unwrap();
-        let diff = vec![diff1];
+        let norm = self.norm(params, x, xprime)?;
+
+        let diff0 = norm / params[0].powi(2);
+        let diff = vec![diff0];
         Ok(diff)
     }
 }
@@ -92,6 +115,41 @@ where
     }
 }
 
+impl StationaryKernel for Exponential {
+    fn variance(&self, params: &[f64]) -> Result<f64, KernelError> {
+        if params.len() != PARAMS_LEN {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+
+        Ok(1.0)
+    }
+
+    /// Multivariate Cauchy distribution of scale `1 / lengthscale`.
+    fn spectral_density(&self, params: &[f64], omega: &[f64]) -> Result<f64, KernelError> {
+        self.variance(params)?;
+
+        Ok(student_t_density(1.0, params[0], omega))
+    }
+
+    fn sample_frequency<R>(
+        &self,
+        params: &[f64],
+        dims: usize,
+        rng: &mut R,
+    ) -> Result<Vec<f64>, KernelError>
+    where
+        R: Rng + ?Sized,
+    {
+        self.variance(params)?;
+
+        sample_student_t(1.0, params[0], dims, rng)
+    }
+
+    fn is_isotropic(&self) -> bool {
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
@@ -105,4 +163,9 @@ mod tests {
 
         assert_eq!(test_value, (-1f64).exp());
     }
+
+    #[test]
+    fn check_gradients() {
+        assert_gradients(&Exponential, &[0.8], 3);
+    }
 }
@@ -0,0 +1,363 @@
+use crate::{
+    Constant, Exponential, KernelAdd, KernelError, KernelMul, Linear, Matern,
+    ParamsDifferentiableKernel, Periodic, PositiveDefiniteKernel, RationalQuadratic,
+    SpectralMixture, ValueDifferentiableKernel, ARD, RBF,
+};
+use std::fmt::{self, Display, Formatter};
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+use std::{ops::Add, ops::Mul};
+
+/// Kernel on `Vec<f64>` whose structure is decided at runtime, e.g. parsed from `"RBF + Constant * Periodic"`.
+///
+/// The grammar is
+/// ```text
+/// expression := term ("+" term)*
+/// term       := factor ("*" factor)*
+/// factor     := kernel | "(" expression ")"
+/// kernel     := "RBF" | "Exponential" | "Periodic" | "Linear" | "Constant" | "RationalQuadratic"
+///             | "ARD(" n ")" | "SpectralMixture(" p "," q ")" | "Matern(" nu ")"
+/// ```
+/// where `+` and `*` are left-associative and `*` binds tighter than `+`.
+/// `Display` writes the same form, so parsing `to_string` gives back a kernel with the same value and
+/// params order. Since both operators are associative, right-nested sums and products are written
+/// without parentheses.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KernelExpression {
+    RBF(RBF),
+    Exponential(Exponential),
+    Periodic(Periodic),
+    Linear(Linear),
+    Constant(Constant),
+    RationalQuadratic(RationalQuadratic),
+    ARD(ARD),
+    SpectralMixture(SpectralMixture),
+    Matern(Matern),
+    Add(Box<KernelAdd<KernelExpression, KernelExpression, Vec<f64>>>),
+    Mul(Box<KernelMul<KernelExpression, KernelExpression, Vec<f64>>>),
+}
+
+macro_rules! dispatch {
+    ($self:expr, $kernel:ident => $e:expr) => {
+        match $self {
+            KernelExpression::RBF($kernel) => $e,
+            KernelExpression::Exponential($kernel) => $e,
+            KernelExpression::Periodic($kernel) => $e,
+            KernelExpression::Linear($kernel) => $e,
+            KernelExpression::Constant($kernel) => $e,
+            KernelExpression::RationalQuadratic($kernel) => $e,
+            KernelExpression::ARD($kernel) => $e,
+            KernelExpression::SpectralMixture($kernel) => $e,
+            KernelExpression::Matern($kernel) => $e,
+            KernelExpression::Add(kernel) => {
+                let $kernel = kernel.as_ref();
+                $e
+            }
+            KernelExpression::Mul(kernel) => {
+                let $kernel = kernel.as_ref();
+                $e
+            }
+        }
+    };
+}
+
+impl Display for KernelExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        dispatch!(self, kernel => Display::fmt(kernel, f))
+    }
+}
+
+impl FromStr for KernelExpression {
+    type Err = KernelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            source: s,
+            chars: s.char_indices().peekable(),
+        };
+        let expression = parser.expression()?;
+
+        match parser.peek() {
+            None => Ok(expression),
+            Some((i, c)) => Err(parser.error(i, &format!("unexpected `{}`", c))),
+        }
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, position: usize, message: &str) -> KernelError {
+        KernelError::InvalidExpression(format!("{} at {} in `{}`", message, position, self.source))
+    }
+
+    fn peek(&mut self) -> Option<(usize, char)> {
+        while let Some(&(_, c)) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+
+        self.chars.peek().copied()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), KernelError> {
+        match self.peek() {
+            Some((_, c)) if c == expected => {
+                self.chars.next();
+                Ok(())
+            }
+            Some((i, c)) => Err(self.error(i, &format!("expected `{}`, found `{}`", expected, c))),
+            None => Err(self.error(
+                self.source.len(),
+                &format!("expected `{}`, found end", expected),
+            )),
+        }
+    }
+
+    fn expression(&mut self) -> Result<KernelExpression, KernelError> {
+        let mut lhs = self.term()?;
+        while let Some((_, '+')) = self.peek() {
+            self.chars.next();
+            let rhs = self.term()?;
+            lhs = KernelExpression::Add(Box::new(KernelAdd::new(lhs, rhs)));
+        }
+
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<KernelExpression, KernelError> {
+        let mut lhs = self.factor()?;
+        while let Some((_, '*')) = self.peek() {
+            self.chars.next();
+            let rhs = self.factor()?;
+            lhs = KernelExpression::Mul(Box::new(KernelMul::new(lhs, rhs)));
+        }
+
+        Ok(lhs)
+    }
+
+    fn factor(&mut self) -> Result<KernelExpression, KernelError> {
+        match self.peek() {
+            Some((_, '(')) => {
+                self.chars.next();
+                let expression = self.expression()?;
+                self.expect(')')?;
+
+                Ok(expression)
+            }
+            Some((i, c)) if c.is_alphabetic() => {
+                let name = self.token(|c| c.is_alphanumeric() || c == '_');
+                self.kernel(i, &name)
+            }
+            Some((i, c)) => Err(self.error(i, &format!("unexpected `{}`", c))),
+            None => Err(self.error(self.source.len(), "unexpected end")),
+        }
+    }
+
+    fn token<F>(&mut self, accept: F) -> String
+    where
+        F: Fn(char) -> bool,
+    {
+        let mut token = String::new();
+        while let Some(&(_, c)) = self.chars.peek() {
+            if !accept(c) {
+                break;
+            }
+            token.push(c);
+            self.chars.next();
+        }
+
+        token
+    }
+
+    /// Parenthesized, comma separated arguments of a kernel.
+    fn arguments<A>(&mut self, len: usize) -> Result<Vec<A>, KernelError>
+    where
+        A: FromStr,
+    {
+        self.expect('(')?;
+        let mut arguments = Vec::with_capacity(len);
+        for i in 0..len {
+            if i != 0 {
+                self.expect(',')?;
+            }
+            let position = self.peek().map_or(self.source.len(), |(i, _)| i);
+            let token = self.token(|c| c.is_alphanumeric() || c == '.' || c == '-' || c == '_');
+            let argument = token
+                .parse()
+                .map_err(|_| self.error(position, &format!("invalid argument `{}`", token)))?;
+            arguments.push(argument);
+        }
+        self.expect(')')?;
+
+        Ok(arguments)
+    }
+
+    fn kernel(&mut self, position: usize, name: &str) -> Result<KernelExpression, KernelError> {
+        let kernel = match name {
+            "RBF" => KernelExpression::RBF(RBF),
+            "Exponential" => KernelExpression::Exponential(Exponential),
+            "Periodic" => KernelExpression::Periodic(Periodic),
+            "Linear" => KernelExpression::Linear(Linear),
+            "Constant" => KernelExpression::Constant(Constant),
+            "RationalQuadratic" => KernelExpression::RationalQuadratic(RationalQuadratic),
+            "ARD" => KernelExpression::ARD(ARD(self.arguments(1)?[0])),
+            "SpectralMixture" => {
+                let arguments = self.arguments(2)?;
+                KernelExpression::SpectralMixture(SpectralMixture::new(arguments[0], arguments[1]))
+            }
+            "Matern" => KernelExpression::Matern(Matern::new(self.arguments(1)?[0])),
+            _ => return Err(self.error(position, &format!("unknown kernel `{}`", name))),
+        };
+
+        Ok(kernel)
+    }
+}
+
+impl PositiveDefiniteKernel<Vec<f64>> for KernelExpression {
+    fn params_len(&self) -> usize {
+        dispatch!(self, kernel => PositiveDefiniteKernel::<Vec<f64>>::params_len(kernel))
+    }
+
+    fn params_names(&self) -> Vec<String> {
+        dispatch!(self, kernel => PositiveDefiniteKernel::<Vec<f64>>::params_names(kernel))
+    }
+
+    fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
+        dispatch!(self, kernel => kernel.value(params, x, xprime))
+    }
+}
+
+impl<R> Add<R> for KernelExpression
+where
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelAdd<Self, R, Vec<f64>>;
+
+    fn add(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<R> Mul<R> for KernelExpression
+where
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelMul<Self, R, Vec<f64>>;
+
+    fn mul(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl ValueDifferentiableKernel<Vec<f64>> for KernelExpression {
+    fn ln_diff_value(
+        &self,
+        params: &[f64],
+        x: &Vec<f64>,
+        xprime: &Vec<f64>,
+    ) -> Result<Vec<f64>, KernelError> {
+        dispatch!(self, kernel => kernel.ln_diff_value(params, x, xprime))
+    }
+}
+
+impl ParamsDifferentiableKernel<Vec<f64>> for KernelExpression {
+    fn ln_diff_params(
+        &self,
+        params: &[f64],
+        x: &Vec<f64>,
+        xprime: &Vec<f64>,
+    ) -> Result<Vec<f64>, KernelError> {
+        dispatch!(self, kernel => kernel.ln_diff_params(params, x, xprime))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let kernel = "RBF + Constant * Periodic"
+            .parse::<KernelExpression>()
+            .unwrap();
+        let params = [1.0, 0.5, 2.0, 0.3, 1.2];
+        let x = vec![0.1, 0.5];
+        let xprime = vec![0.4, -0.2];
+
+        let expected = (RBF + Constant * Periodic)
+            .value(&params, &x, &xprime)
+            .unwrap();
+
+        assert_eq!(kernel.value(&params, &x, &xprime).unwrap(), expected);
+        assert_eq!(
+            kernel.params_names(),
+            (RBF + Constant * Periodic).params_names()
+        );
+    }
+
+    #[test]
+    fn it_works2() {
+        let sources = [
+            "RBF + Constant * Periodic",
+            "(RBF + Linear) * Exponential",
+            "ARD(3) * (SpectralMixture(3, 2) + Matern(1.5)) + RationalQuadratic",
+            "Constant * (RBF + Periodic + Linear)",
+        ];
+
+        for source in sources.iter() {
+            let kernel = source.parse::<KernelExpression>().unwrap();
+
+            assert_eq!(&kernel.to_string(), source);
+        }
+
+        assert_eq!(
+            (RBF * (Constant + Linear)).to_string(),
+            "RBF * (Constant + Linear)"
+        );
+        assert_eq!(
+            " ( RBF+Matern(2.5 ) )*ARD( 2)"
+                .parse::<KernelExpression>()
+                .unwrap()
+                .to_string(),
+            "(RBF + Matern(2.5)) * ARD(2)"
+        );
+    }
+
+    #[test]
+    fn it_works3() {
+        for source in [
+            "",
+            "RBF +",
+            "RBF * (Linear",
+            "Gaussian",
+            "ARD",
+            "ARD(x)",
+            "SpectralMixture(1)",
+            "RBF Linear",
+        ]
+        .iter()
+        {
+            match source.parse::<KernelExpression>() {
+                Err(KernelError::InvalidExpression(_)) => (),
+                _ => panic!("{}", source),
+            };
+        }
+    }
+
+    #[test]
+    fn check_gradients() {
+        let kernel = "ARD(2) * (RBF + Periodic) + Constant"
+            .parse::<KernelExpression>()
+            .unwrap();
+        let params = [0.7, 1.3, 1.0, 0.5, 0.4, 1.1, 0.2];
+
+        assert_gradients(&kernel, &params, 2);
+    }
+}
@@ -0,0 +1,299 @@
+use crate::linalg::{cholesky, cholesky_inverse, cholesky_solve, forward_substitution};
+use crate::{Adam, KernelError, ParamsDifferentiableKernel, PositiveDefiniteKernel, Value};
+use opensrdk_linear_algebra::Matrix;
+use rayon::prelude::*;
+use std::f64::consts::PI;
+
+/// Zero-mean Gaussian process regression with Gaussian noise, `y = f(x) + ε`, `ε ~ N(0, σ²)`.
+///
+/// The posterior and the log marginal likelihood go through the Cholesky factor `L` of `K + σ² I`.
+/// `search` fits the params and `σ²` by maximizing the log marginal likelihood.
+/// http://www.gaussianprocess.org/gpml/chapters/RW2.pdf
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GaussianProcessRegressor<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    kernel: K,
+    params: Vec<f64>,
+    noise_variance: f64,
+    x: Vec<T>,
+    l: Matrix,
+    alpha: Vec<f64>,
+    log_marginal_likelihood: f64,
+}
+
+/// `L` with `L L^T = K + σ² I`, `α = (K + σ² I)^-1 y` and the log marginal likelihood.
+fn factorize<K, T>(
+    kernel: &K,
+    params: &[f64],
+    x: &[T],
+    y: &[f64],
+    noise_variance: f64,
+) -> Result<(Matrix, Vec<f64>, f64), KernelError>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    if x.len() != y.len() || x.is_empty() {
+        return Err(KernelError::InvalidArgument);
+    }
+    if noise_variance.is_nan() || noise_variance <= 0.0 {
+        return Err(KernelError::InvalidParameter);
+    }
+
+    let n = x.len();
+    let mut c = kernel.gram_matrix(params, x)?;
+    for i in 0..n {
+        c[(i, i)] += noise_variance;
+    }
+    let l = cholesky(&c)?;
+    let alpha = cholesky_solve(&l, y);
+
+    let log_marginal_likelihood = -0.5
+        * y.iter().zip(alpha.iter()).map(|(y, a)| y * a).sum::<f64>()
+        - (0..n).map(|i| l[(i, i)].ln()).sum::<f64>()
+        - 0.5 * n as f64 * (2.0 * PI).ln();
+
+    Ok((l, alpha, log_marginal_likelihood))
+}
+
+impl<K, T> GaussianProcessRegressor<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    pub fn fit(
+        kernel: K,
+        params: &[f64],
+        x: Vec<T>,
+        y: &[f64],
+        noise_variance: f64,
+    ) -> Result<Self, KernelError> {
+        let (l, alpha, log_marginal_likelihood) =
+            factorize(&kernel, params, &x, y, noise_variance)?;
+
+        Ok(Self {
+            kernel,
+            params: params.to_vec(),
+            noise_variance,
+            x,
+            l,
+            alpha,
+            log_marginal_likelihood,
+        })
+    }
+
+    pub fn kernel_ref(&self) -> &K {
+        &self.kernel
+    }
+
+    pub fn params(&self) -> &[f64] {
+        &self.params
+    }
+
+    pub fn noise_variance(&self) -> f64 {
+        self.noise_variance
+    }
+
+    pub fn x(&self) -> &[T] {
+        &self.x
+    }
+
+    /// `(K + σ² I)^-1 y`
+    pub fn alpha(&self) -> &[f64] {
+        &self.alpha
+    }
+
+    /// `ln p(y | X) = -y^T α / 2 - Σ_i ln L_ii - n ln(2π) / 2`
+    pub fn log_marginal_likelihood(&self) -> f64 {
+        self.log_marginal_likelihood
+    }
+
+    /// Posterior mean and variance of `f` at each new point. Add `noise_variance` for the predictive variance of `y`.
+    pub fn predict(&self, xnew: &[T]) -> Result<(Vec<f64>, Vec<f64>), KernelError> {
+        let (mean, variance) = xnew
+            .par_iter()
+            .map(|xi| {
+                let k = self
+                    .x
+                    .iter()
+                    .map(|xj| self.kernel.value(&self.params, xi, xj))
+                    .collect::<Result<Vec<f64>, KernelError>>()?;
+                let mean = k.iter().zip(self.alpha.iter()).map(|(k, a)| k * a).sum();
+                let v = forward_substitution(&self.l, &k);
+                let variance = self.kernel.value(&self.params, xi, xi)?
+                    - v.iter().map(|v| v.powi(2)).sum::<f64>();
+
+                Ok((mean, variance.max(0.0)))
+            })
+            .collect::<Result<Vec<(f64, f64)>, KernelError>>()?
+            .into_iter()
+            .unzip();
+
+        Ok((mean, variance))
+    }
+}
+
+impl<K, T> GaussianProcessRegressor<K, T>
+where
+    K: ParamsDifferentiableKernel<T>,
+    T: Value,
+{
+    /// Log marginal likelihood and its gradient with respect to `[params..., ln σ²]`,
+    /// `∂ ln p(y | X) / ∂θ = tr((α α^T - (K + σ² I)^-1) ∂(K + σ² I) / ∂θ) / 2`.
+    pub fn log_marginal_likelihood_gradient(
+        kernel: &K,
+        params: &[f64],
+        x: &[T],
+        y: &[f64],
+        noise_variance: f64,
+    ) -> Result<(f64, Vec<f64>), KernelError> {
+        let n = x.len();
+        let (l, alpha, log_marginal_likelihood) = factorize(kernel, params, x, y, noise_variance)?;
+        let c_inv = cholesky_inverse(&l);
+
+        let mut w = Matrix::new(n, n);
+        w.elems_mut()
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(index, wij)| {
+                let (i, j) = (index % n, index / n);
+                *wij = alpha[i] * alpha[j] - c_inv[(i, j)];
+            });
+
+        let diff_params = kernel
+            .gram_matrix_diff_params(params, x)?
+            .par_iter()
+            .map(|dk| {
+                0.5 * w
+                    .elems()
+                    .iter()
+                    .zip(dk.elems().iter())
+                    .map(|(w, dk)| w * dk)
+                    .sum::<f64>()
+            })
+            .collect::<Vec<f64>>();
+        let diff_noise = 0.5 * noise_variance * (0..n).map(|i| w[(i, i)]).sum::<f64>();
+
+        Ok((
+            log_marginal_likelihood,
+            [diff_params, vec![diff_noise]].concat(),
+        ))
+    }
+
+    /// Fits with the params and `σ²` that maximize the log marginal likelihood by `Adam`, starting from the given ones.
+    pub fn search(
+        kernel: K,
+        params: &[f64],
+        x: Vec<T>,
+        y: &[f64],
+        noise_variance: f64,
+        adam: &Adam,
+    ) -> Result<Self, KernelError> {
+        let (params, noise_variance) =
+            adam.minimize_with_log_scale(params, noise_variance, |params, noise_variance| {
+                let (lml, gradient) =
+                    Self::log_marginal_likelihood_gradient(&kernel, params, &x, y, noise_variance)?;
+
+                Ok((-lml, gradient.into_iter().map(|g| -g).collect()))
+            })?;
+
+        Self::fit(kernel, &params, x, y, noise_variance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::prelude::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn it_works() {
+        let x = vec![vec![-1.0], vec![0.2], vec![1.5]];
+        let y = [0.3, -0.4, 1.1];
+        let params = [1.2, 0.8];
+        let noise_variance = 0.1;
+
+        let gp =
+            GaussianProcessRegressor::fit(RBF, &params, x.clone(), &y, noise_variance).unwrap();
+        let (mean, variance) = gp.predict(&[vec![0.2], vec![10.0]]).unwrap();
+
+        let k = RBF.gram_matrix(&params, &x).unwrap();
+        let c = |i: usize, j: usize| k[(i, j)] + if i == j { noise_variance } else { 0.0 };
+        let det = c(0, 0) * (c(1, 1) * c(2, 2) - c(1, 2) * c(2, 1))
+            - c(0, 1) * (c(1, 0) * c(2, 2) - c(1, 2) * c(2, 0))
+            + c(0, 2) * (c(1, 0) * c(2, 1) - c(1, 1) * c(2, 0));
+        let y_alpha = (0..3).map(|i| y[i] * gp.alpha()[i]).sum::<f64>();
+        let expected = -0.5 * y_alpha - 0.5 * det.ln() - 1.5 * (2.0 * PI).ln();
+
+        assert!((gp.log_marginal_likelihood() - expected).abs() < 1e-12);
+        assert!((mean[0] - y[1]).abs() < 0.3);
+        assert!(variance[0] < noise_variance);
+        assert!(mean[1].abs() < 1e-10);
+        assert!((variance[1] - params[0]).abs() < 1e-10);
+    }
+
+    #[test]
+    fn check_gradients() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let x = (0..15)
+            .map(|_| vec![rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0)])
+            .collect::<Vec<Vec<f64>>>();
+        let y = x
+            .iter()
+            .map(|xi| (xi[0] - xi[1]).sin())
+            .collect::<Vec<f64>>();
+        let kernel = RBF + Constant * Linear;
+        let params = [1.0, 0.8, 0.3];
+        let noise_variance = 0.05;
+
+        let (_, gradient) = GaussianProcessRegressor::log_marginal_likelihood_gradient(
+            &kernel,
+            &params,
+            &x,
+            &y,
+            noise_variance,
+        )
+        .unwrap();
+        assert_log_scale_gradient(
+            &gradient,
+            &params,
+            noise_variance,
+            |params, noise_variance| {
+                GaussianProcessRegressor::fit(kernel.clone(), params, x.clone(), &y, noise_variance)
+                    .unwrap()
+                    .log_marginal_likelihood()
+            },
+        );
+    }
+
+    #[test]
+    fn it_works2() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let x = (0..30)
+            .map(|_| vec![rng.gen_range(-3.0..3.0)])
+            .collect::<Vec<Vec<f64>>>();
+        let y = x
+            .iter()
+            .map(|xi| xi[0].sin() + 0.1 * rng.gen_range(-1.0..1.0))
+            .collect::<Vec<f64>>();
+        let kernel =
+            Transformed::new(RBF, vec![ParamsTransform::Exp, ParamsTransform::Exp]).unwrap();
+        let params = [0.0, 2.0];
+
+        let initial =
+            GaussianProcessRegressor::fit(kernel.clone(), &params, x.clone(), &y, 1.0).unwrap();
+        let searched =
+            GaussianProcessRegressor::search(kernel, &params, x, &y, 1.0, &Adam::new(0.1, 100))
+                .unwrap();
+        let (mean, _) = searched.predict(&[vec![1.0]]).unwrap();
+
+        assert!(searched.log_marginal_likelihood() > initial.log_marginal_likelihood());
+        assert!(searched.noise_variance() < 0.1);
+        assert!((mean[0] - 1f64.sin()).abs() < 0.1);
+    }
+}
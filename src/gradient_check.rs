@@ -0,0 +1,182 @@
+use crate::{KernelError, ParamsDifferentiableKernel, Value, ValueDifferentiableKernel};
+
+const STEP: f64 = 1e-6;
+
+/// Comparison of an analytic gradient of `ln k` with central finite differences of `value`.
+#[derive(Clone, Debug)]
+pub struct GradientCheck {
+    pub analytic: Vec<f64>,
+    pub numerical: Vec<f64>,
+    /// `|analytic - numerical| / max(|analytic|, |numerical|, 1)` for each component:
+    /// relative for components larger than 1, absolute otherwise.
+    pub errors: Vec<f64>,
+}
+
+impl GradientCheck {
+    fn new(analytic: Vec<f64>, numerical: Vec<f64>) -> Result<Self, KernelError> {
+        if analytic.len() != numerical.len() {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let errors = analytic
+            .iter()
+            .zip(numerical.iter())
+            .map(|(a, n)| (a - n).abs() / a.abs().max(n.abs()).max(1.0))
+            .collect();
+
+        Ok(Self {
+            analytic,
+            numerical,
+            errors,
+        })
+    }
+
+    pub fn max_error(&self) -> f64 {
+        self.errors.iter().fold(0.0, |max, &e| max.max(e))
+    }
+}
+
+/// Numerical `d ln k / dv` by central differences, where `f(v)` evaluates `k` at the perturbed point.
+fn central_differences<F>(point: &[f64], fx: f64, f: F) -> Result<Vec<f64>, KernelError>
+where
+    F: Fn(&[f64]) -> Result<f64, KernelError>,
+{
+    let mut perturbed = point.to_vec();
+
+    (0..point.len())
+        .map(|i| {
+            let h = STEP * (1.0 + point[i].abs());
+            perturbed[i] = point[i] + h;
+            let fplus = f(&perturbed)?;
+            perturbed[i] = point[i] - h;
+            let fminus = f(&perturbed)?;
+            perturbed[i] = point[i];
+
+            Ok((fplus - fminus) / (2.0 * h * fx))
+        })
+        .collect()
+}
+
+/// Checks `ln_diff_params` against central finite differences of `value` with respect to `params`.
+pub fn check_params_gradient<K, T>(
+    kernel: &K,
+    params: &[f64],
+    x: &T,
+    xprime: &T,
+) -> Result<GradientCheck, KernelError>
+where
+    K: ParamsDifferentiableKernel<T>,
+    T: Value,
+{
+    let analytic = kernel.ln_diff_params(params, x, xprime)?;
+    let fx = kernel.value(params, x, xprime)?;
+    let numerical = central_differences(params, fx, |p| kernel.value(p, x, xprime))?;
+
+    GradientCheck::new(analytic, numerical)
+}
+
+/// Checks `ln_diff_value` against central finite differences of `value` with respect to `x`.
+pub fn check_value_gradient<K>(
+    kernel: &K,
+    params: &[f64],
+    x: &Vec<f64>,
+    xprime: &Vec<f64>,
+) -> Result<GradientCheck, KernelError>
+where
+    K: ValueDifferentiableKernel<Vec<f64>>,
+{
+    let analytic = kernel.ln_diff_value(params, x, xprime)?;
+    let fx = kernel.value(params, x, xprime)?;
+    let numerical = central_differences(x, fx, |x| kernel.value(params, &x.to_vec(), xprime))?;
+
+    GradientCheck::new(analytic, numerical)
+}
+
+/// Asserts both gradients of `kernel` at `params` for random pairs of `dims`-dimensional points.
+/// The points stay away from the origin so that kernels such as `Linear` do not vanish.
+#[cfg(test)]
+pub(crate) fn assert_gradients<K>(kernel: &K, params: &[f64], dims: usize)
+where
+    K: ParamsDifferentiableKernel<Vec<f64>> + ValueDifferentiableKernel<Vec<f64>>,
+{
+    use rand::prelude::*;
+
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..20 {
+        let x = (0..dims)
+            .map(|_| rng.gen_range(0.5..1.5))
+            .collect::<Vec<f64>>();
+        let xprime = (0..dims)
+            .map(|_| rng.gen_range(0.5..1.5))
+            .collect::<Vec<f64>>();
+
+        let params_check = check_params_gradient(kernel, params, &x, &xprime).unwrap();
+        let value_check = check_value_gradient(kernel, params, &x, &xprime).unwrap();
+
+        assert!(params_check.max_error() < 1e-6, "{:?}", params_check);
+        assert!(value_check.max_error() < 1e-6, "{:?}", value_check);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let kernel = RBF;
+
+        let check =
+            check_params_gradient(&kernel, &[1.5, 2.0], &vec![0.1, 0.2], &vec![0.3, -0.4]).unwrap();
+
+        assert_eq!(check.analytic.len(), 2);
+        assert!(check.max_error() < 1e-6);
+    }
+
+    #[test]
+    fn it_works2() {
+        let kernel = InstantKernel::new(1, |params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>| {
+            Ok(params[0] * x[0] * xprime[0])
+        });
+        let wrong = WrongGradient(kernel);
+
+        let check = check_value_gradient(&wrong, &[2.0], &vec![0.5], &vec![1.5]).unwrap();
+
+        assert!(check.max_error() > 0.1);
+    }
+
+    #[derive(Clone, Debug)]
+    struct WrongGradient<K>(K);
+
+    impl<K> PositiveDefiniteKernel<Vec<f64>> for WrongGradient<K>
+    where
+        K: PositiveDefiniteKernel<Vec<f64>>,
+    {
+        fn params_len(&self) -> usize {
+            self.0.params_len()
+        }
+
+        fn value(
+            &self,
+            params: &[f64],
+            x: &Vec<f64>,
+            xprime: &Vec<f64>,
+        ) -> Result<f64, KernelError> {
+            self.0.value(params, x, xprime)
+        }
+    }
+
+    impl<K> ValueDifferentiableKernel<Vec<f64>> for WrongGradient<K>
+    where
+        K: PositiveDefiniteKernel<Vec<f64>>,
+    {
+        fn ln_diff_value(
+            &self,
+            _params: &[f64],
+            x: &Vec<f64>,
+            _xprime: &Vec<f64>,
+        ) -> Result<Vec<f64>, KernelError> {
+            Ok(vec![0.0; x.len()])
+        }
+    }
+}
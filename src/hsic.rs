@@ -0,0 +1,278 @@
+use crate::linalg::centre;
+use crate::special::regularized_lower_gamma;
+use crate::{KernelError, PositiveDefiniteKernel, Value};
+use opensrdk_linear_algebra::Matrix;
+use rand::prelude::*;
+use rayon::prelude::*;
+
+/// Kernel independence test with the Hilbert-Schmidt independence criterion between paired samples `(x_i, y_i)`,
+/// each with its own kernel and possibly of different types.
+/// HSIC is the squared norm of the cross-covariance operator, which is zero if and only if `x` and `y`
+/// are independent for characteristic kernels.
+///
+/// The Gram matrices are computed once and shared by the estimators and the p-values.
+/// https://papers.nips.cc/paper/2007/hash/d5cfead94f5350c12c322b5b664544c1-Abstract.html
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HilbertSchmidtIndependence {
+    k: Matrix,
+    l: Matrix,
+    /// `H K H` with `H = I - 1/n`.
+    kc: Matrix,
+    /// `H L H`
+    lc: Matrix,
+}
+
+/// `Σ_ij a_ij b_ij`, which is `tr(A B)` for symmetric matrices.
+fn frobenius(a: &Matrix, b: &Matrix) -> f64 {
+    a.elems()
+        .par_iter()
+        .zip(b.elems().par_iter())
+        .map(|(a, b)| a * b)
+        .sum()
+}
+
+/// Mean of the diagonal minus the mean of the off-diagonal elements, an estimate of `E[k(x, x)] - E[k(x, x')]`.
+fn diagonal_excess(k: &Matrix) -> f64 {
+    let n = k.rows();
+    let diagonal = (0..n).map(|i| k[(i, i)]).sum::<f64>();
+
+    diagonal / n as f64 - (k.elems().iter().sum::<f64>() - diagonal) / (n * (n - 1)) as f64
+}
+
+impl HilbertSchmidtIndependence {
+    /// `x` and `y` are paired, so they need the same length, and at least 4 points for the unbiased estimator.
+    pub fn new<KX, X, KY, Y>(
+        kernel_x: &KX,
+        params_x: &[f64],
+        x: &[X],
+        kernel_y: &KY,
+        params_y: &[f64],
+        y: &[Y],
+    ) -> Result<Self, KernelError>
+    where
+        KX: PositiveDefiniteKernel<X>,
+        X: Value,
+        KY: PositiveDefiniteKernel<Y>,
+        Y: Value,
+    {
+        if x.len() != y.len() || x.len() < 4 {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let k = kernel_x.gram_matrix(params_x, x)?;
+        let l = kernel_y.gram_matrix(params_y, y)?;
+        let kc = centre(&k);
+        let lc = centre(&l);
+
+        Ok(Self { k, l, kc, lc })
+    }
+
+    pub fn len(&self) -> usize {
+        self.k.rows()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Biased estimator `HSIC_b = tr(K H L H) / n²`.
+    pub fn biased(&self) -> f64 {
+        frobenius(&self.kc, &self.l) / (self.len() as f64).powi(2)
+    }
+
+    /// Unbiased estimator of Song et al.,
+    /// `HSIC_u = (tr(K~ L~) + 1^T K~ 1 1^T L~ 1 / (n - 1)(n - 2) - 2 1^T K~ L~ 1 / (n - 2)) / n(n - 3)`,
+    /// where `K~` and `L~` are `K` and `L` with zero diagonals. It can be negative under independence.
+    /// https://www.jmlr.org/papers/volume13/song12a/song12a.pdf
+    pub fn unbiased(&self) -> f64 {
+        let n = self.len();
+        let without_diagonal = |k: &Matrix| {
+            let mut k = k.clone();
+            for i in 0..n {
+                k[(i, i)] = 0.0;
+            }
+            k
+        };
+        let k = without_diagonal(&self.k);
+        let l = without_diagonal(&self.l);
+
+        let k_sums = (0..n)
+            .map(|i| (0..n).map(|j| k[(i, j)]).sum::<f64>())
+            .collect::<Vec<f64>>();
+        let l_sums = (0..n)
+            .map(|i| (0..n).map(|j| l[(i, j)]).sum::<f64>())
+            .collect::<Vec<f64>>();
+        let k_sum = k_sums.iter().sum::<f64>();
+        let l_sum = l_sums.iter().sum::<f64>();
+        let kl_sum = k_sums
+            .iter()
+            .zip(l_sums.iter())
+            .map(|(k, l)| k * l)
+            .sum::<f64>();
+        let n = n as f64;
+
+        (frobenius(&k, &l) + k_sum * l_sum / ((n - 1.0) * (n - 2.0)) - 2.0 * kl_sum / (n - 2.0))
+            / (n * (n - 3.0))
+    }
+
+    /// Normalized HSIC, or centred kernel alignment, `tr(K H L H) / sqrt(tr(K H K H) tr(L H L H))`, in `[0, 1]`.
+    pub fn cka(&self) -> Result<f64, KernelError> {
+        let norm = (frobenius(&self.kc, &self.kc) * frobenius(&self.lc, &self.lc)).sqrt();
+        if norm <= 0.0 {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        Ok(frobenius(&self.kc, &self.lc) / norm)
+    }
+
+    /// p-value of `n HSIC_b` under the gamma distribution matching its null mean and variance.
+    /// The null mean is `(E[k(x, x)] - E[k(x, x')]) (E[l(y, y)] - E[l(y, y')]) / n`, which reduces to
+    /// `(1 - μ_x)(1 - μ_y) / n` of Gretton et al. for kernels with unit diagonals. Needs at least 6 points.
+    pub fn gamma_p_value(&self) -> Result<f64, KernelError> {
+        let n = self.len();
+        if n < 6 {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let statistic = n as f64 * self.biased();
+
+        let variance = self
+            .kc
+            .elems()
+            .par_iter()
+            .zip(self.lc.elems().par_iter())
+            .enumerate()
+            .filter(|(index, _)| index % n != index / n)
+            .map(|(_, (k, l))| (k * l / 6.0).powi(2))
+            .sum::<f64>()
+            / (n * (n - 1)) as f64;
+        let n = n as f64;
+        let variance =
+            72.0 * (n - 4.0) * (n - 5.0) / (n * (n - 1.0) * (n - 2.0) * (n - 3.0)) * variance;
+
+        let mean = diagonal_excess(&self.k) * diagonal_excess(&self.l) / n;
+        if variance.is_nan() || variance <= 0.0 || mean.is_nan() || mean <= 0.0 {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let shape = mean.powi(2) / variance;
+        let scale = n * variance / mean;
+
+        Ok(1.0 - regularized_lower_gamma(shape, statistic / scale))
+    }
+
+    /// p-value of `HSIC_b` against `permutations` random re-pairings of `y` with `x`.
+    pub fn permutation_p_value<R>(&self, permutations: usize, rng: &mut R) -> f64
+    where
+        R: Rng + ?Sized,
+    {
+        let n = self.len();
+        let statistic = frobenius(&self.kc, &self.l);
+        let mut index = (0..n).collect::<Vec<usize>>();
+        let exceeded = (0..permutations)
+            .filter(|_| {
+                index.shuffle(rng);
+                let permuted = (0..n)
+                    .into_par_iter()
+                    .map(|j| {
+                        (0..n)
+                            .map(|i| self.kc[(i, j)] * self.l[(index[i], index[j])])
+                            .sum::<f64>()
+                    })
+                    .sum::<f64>();
+                permuted >= statistic
+            })
+            .count();
+
+        (exceeded + 1) as f64 / (permutations + 1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::prelude::*;
+    use rand_distr::StandardNormal;
+
+    #[test]
+    fn it_works() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let x = (0..8)
+            .map(|_| vec![rng.sample(StandardNormal)])
+            .collect::<Vec<Vec<f64>>>();
+        let y = (0..8)
+            .map(|_| vec![rng.sample(StandardNormal)])
+            .collect::<Vec<Vec<f64>>>();
+        let params = [1.0, 1.0];
+
+        let hsic = HilbertSchmidtIndependence::new(&RBF, &params, &x, &RBF, &params, &y).unwrap();
+
+        // Unbiased estimator as the mean of the U-statistic kernel over distinct quadruples.
+        let k = |i: usize, j: usize| RBF.value(&params, &x[i], &x[j]).unwrap();
+        let l = |i: usize, j: usize| RBF.value(&params, &y[i], &y[j]).unwrap();
+        let mut sum = 0.0;
+        let mut count = 0;
+        for i in 0..8 {
+            for j in 0..8 {
+                for q in 0..8 {
+                    for r in 0..8 {
+                        let distinct = [i, j, q, r];
+                        if (0..4).any(|a| (a + 1..4).any(|b| distinct[a] == distinct[b])) {
+                            continue;
+                        }
+                        sum += k(i, j) * (l(i, j) + l(q, r) - 2.0 * l(i, q));
+                        count += 1;
+                    }
+                }
+            }
+        }
+
+        assert!((hsic.unbiased() - sum / count as f64).abs() < 1e-12);
+        assert!(hsic.biased() > 0.0);
+        let same = HilbertSchmidtIndependence::new(&RBF, &params, &x, &RBF, &params, &x).unwrap();
+        assert!((same.cka().unwrap() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn it_works2() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let x = (0..60)
+            .map(|_| vec![rng.gen_range(-1.0..1.0)])
+            .collect::<Vec<Vec<f64>>>();
+        let sequence = |rng: &mut StdRng, level: f64| {
+            Sequence(
+                (0..3)
+                    .map(|_| vec![level + 0.1 * rng.sample::<f64, _>(StandardNormal)])
+                    .collect(),
+            )
+        };
+        let dependent = x
+            .iter()
+            .map(|xi| sequence(&mut rng, xi[0].powi(2)))
+            .collect::<Vec<Sequence>>();
+        let independent = x
+            .iter()
+            .map(|_| {
+                let level = rng.gen_range(0.0..1.0);
+                sequence(&mut rng, level)
+            })
+            .collect::<Vec<Sequence>>();
+        let kernel = Convolutional::new(RBF);
+        let params = [1.0, 0.5];
+
+        let null =
+            HilbertSchmidtIndependence::new(&RBF, &params, &x, &kernel, &params, &independent)
+                .unwrap();
+        let alternative =
+            HilbertSchmidtIndependence::new(&RBF, &params, &x, &kernel, &params, &dependent)
+                .unwrap();
+
+        assert!(null.gamma_p_value().unwrap() > 0.05);
+        assert!(null.permutation_p_value(200, &mut rng) > 0.05);
+        assert!(alternative.gamma_p_value().unwrap() < 0.01);
+        assert!(alternative.permutation_p_value(200, &mut rng) < 0.01);
+        assert!(alternative.cka().unwrap() > null.cka().unwrap());
+        assert!(alternative.unbiased() > null.unbiased());
+    }
+}
@@ -100,4 +100,11 @@ mod tests {
 
         println!("{}", test_value);
     }
+
+    #[test]
+    fn it_works2() {
+        let kernel = InstantKernel::new(2, |_: &[f64], _: &Vec<f64>, _: &Vec<f64>| Ok(0.0));
+
+        assert_eq!(kernel.params_names(), vec!["param0", "param1"]);
+    }
 }
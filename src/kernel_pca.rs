@@ -0,0 +1,291 @@
+use crate::linalg::{centre, column_means, symmetric_eigen};
+use crate::{KernelError, PositiveDefiniteKernel, StationaryKernel, Value};
+use opensrdk_linear_algebra::Matrix;
+use rayon::prelude::*;
+
+/// Kernel principal component analysis.
+///
+/// The Gram matrix is centred in the feature space, `K~ = (I - 1/n) K (I - 1/n)`, and the components are
+/// its leading eigenvectors scaled by `1 / sqrt(λ)`, so that they have unit norm in the feature space.
+/// New points are centred with the means of the training Gram matrix before being projected.
+///
+/// With the `lapack` feature the eigendecomposition is `sytrd` followed by `stev` of opensrdk-linear-algebra,
+/// which needs a LAPACK backend to be linked. Otherwise it is the crate's own cyclic Jacobi method,
+/// which is slower on large Gram matrices, as each sweep is `O(n³)`.
+/// `fit` fails with `NotConverged` when the eigendecomposition does not converge.
+/// https://www.face-rec.org/algorithms/Kernel/kernelPCA_scholkopf.pdf
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KernelPCA<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    kernel: K,
+    params: Vec<f64>,
+    x: Vec<T>,
+    /// `Σ_i K_ij / n` for each `j`.
+    column_means: Vec<f64>,
+    /// `Σ_ij K_ij / n²`
+    mean: f64,
+    eigenvalues: Vec<f64>,
+    /// `n x components` matrix whose columns are the coefficients of the components.
+    alphas: Matrix,
+}
+
+impl<K, T> KernelPCA<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    /// Keeps at most `components` components, leaving out those whose eigenvalue is not positive.
+    pub fn fit(
+        kernel: K,
+        params: &[f64],
+        x: Vec<T>,
+        components: usize,
+    ) -> Result<Self, KernelError> {
+        let n = x.len();
+        if n == 0 || components == 0 {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let k = kernel.gram_matrix(params, &x)?;
+        let column_means = column_means(&k);
+        let mean = column_means.iter().sum::<f64>() / n as f64;
+
+        let (e, u) = symmetric_eigen(&centre(&k))?;
+        let threshold = e.first().map_or(0.0, |e| e.abs() * 1e-12);
+        let components = e
+            .iter()
+            .take(components)
+            .take_while(|&&e| e > threshold)
+            .count();
+
+        let mut alphas = Matrix::new(n, components);
+        for c in 0..components {
+            let scale = e[c].sqrt().recip();
+            for i in 0..n {
+                alphas[(i, c)] = u[(i, c)] * scale;
+            }
+        }
+
+        Ok(Self {
+            kernel,
+            params: params.to_vec(),
+            x,
+            column_means,
+            mean,
+            eigenvalues: e[..components].to_vec(),
+            alphas,
+        })
+    }
+
+    pub fn kernel_ref(&self) -> &K {
+        &self.kernel
+    }
+
+    pub fn params(&self) -> &[f64] {
+        &self.params
+    }
+
+    pub fn x(&self) -> &[T] {
+        &self.x
+    }
+
+    pub fn components(&self) -> usize {
+        self.alphas.cols()
+    }
+
+    /// Eigenvalues of the centred Gram matrix for the kept components, in descending order.
+    pub fn eigenvalues(&self) -> &[f64] {
+        &self.eigenvalues
+    }
+
+    /// Coefficients of the components on the training points, one column per component.
+    pub fn alphas(&self) -> &Matrix {
+        &self.alphas
+    }
+
+    /// Projections of new points onto the components, one row per point.
+    pub fn transform(&self, xnew: &[T]) -> Result<Matrix, KernelError> {
+        let n = self.x.len();
+        let projections = xnew
+            .par_iter()
+            .map(|xi| {
+                let k = self
+                    .x
+                    .iter()
+                    .map(|xj| self.kernel.value(&self.params, xi, xj))
+                    .collect::<Result<Vec<f64>, KernelError>>()?;
+                let row_mean = k.iter().sum::<f64>() / n as f64;
+                let centred = k
+                    .iter()
+                    .zip(self.column_means.iter())
+                    .map(|(k, column_mean)| k - row_mean - column_mean + self.mean)
+                    .collect::<Vec<f64>>();
+
+                Ok((0..self.components())
+                    .map(|c| (0..n).map(|j| centred[j] * self.alphas[(j, c)]).sum())
+                    .collect::<Vec<f64>>())
+            })
+            .collect::<Result<Vec<Vec<f64>>, KernelError>>()?;
+
+        let mut z = Matrix::new(xnew.len(), self.components());
+        for (i, projection) in projections.into_iter().enumerate() {
+            for (c, p) in projection.into_iter().enumerate() {
+                z[(i, c)] = p;
+            }
+        }
+
+        Ok(z)
+    }
+}
+
+impl<K> KernelPCA<K, Vec<f64>>
+where
+    K: StationaryKernel,
+{
+    /// Approximate pre-image of `projection` by the fixed-point iteration of Mika et al.,
+    /// `z <- Σ_i γ_i k(z, x_i) x_i / Σ_i γ_i k(z, x_i)`, where `γ` are the weights of the point in the
+    /// feature space. The update is derived for `RBF`; for other isotropic kernels it is a heuristic.
+    /// Starts from the training point whose projection is closest.
+    /// https://papers.nips.cc/paper/1998/hash/226d1f15ecd35f784d2a20c3ecf56d7f-Abstract.html
+    pub fn pre_image(
+        &self,
+        projection: &[f64],
+        max_iterations: usize,
+    ) -> Result<Vec<f64>, KernelError> {
+        if !self.kernel.is_isotropic() {
+            return Err(KernelError::InvalidArgument);
+        }
+        if projection.len() != self.components() {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let n = self.x.len();
+        let beta = (0..n)
+            .map(|i| {
+                (0..self.components())
+                    .map(|c| projection[c] * self.alphas[(i, c)])
+                    .sum::<f64>()
+            })
+            .collect::<Vec<f64>>();
+        let beta_mean = beta.iter().sum::<f64>() / n as f64;
+        let gamma = beta
+            .iter()
+            .map(|b| b - beta_mean + 1.0 / n as f64)
+            .collect::<Vec<f64>>();
+
+        let training = self.transform(&self.x)?;
+        let nearest = (0..n)
+            .map(|i| {
+                let d = (0..self.components())
+                    .map(|c| (training[(i, c)] - projection[c]).powi(2))
+                    .sum::<f64>();
+                (i, d)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+            .ok_or(KernelError::InvalidArgument)?;
+
+        let mut z = self.x[nearest].clone();
+        for _ in 0..max_iterations {
+            let weights = self
+                .x
+                .iter()
+                .zip(gamma.iter())
+                .map(|(xi, g)| Ok(g * self.kernel.value(&self.params, &z, xi)?))
+                .collect::<Result<Vec<f64>, KernelError>>()?;
+            let sum = weights.iter().sum::<f64>();
+            if sum.abs() < f64::EPSILON {
+                break;
+            }
+
+            let next = (0..z.len())
+                .map(|d| {
+                    weights
+                        .iter()
+                        .zip(self.x.iter())
+                        .map(|(w, xi)| w * xi[d])
+                        .sum::<f64>()
+                        / sum
+                })
+                .collect::<Vec<f64>>();
+            let step = next
+                .iter()
+                .zip(z.iter())
+                .map(|(a, b)| (a - b).powi(2))
+                .sum::<f64>();
+            z = next;
+            if step < 1e-20 {
+                break;
+            }
+        }
+
+        Ok(z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::prelude::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn it_works() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let x = (0..40)
+            .map(|_| {
+                let t = rng.gen_range(-2.0..2.0);
+                vec![1.0 + 2.0 * t, -1.0 + t + 0.01 * rng.gen_range(-1.0..1.0)]
+            })
+            .collect::<Vec<Vec<f64>>>();
+
+        let pca = KernelPCA::fit(Linear, &[], x.clone(), 2).unwrap();
+        let z = pca.transform(&x).unwrap();
+
+        assert!(pca.eigenvalues()[1] < 1e-3 * pca.eigenvalues()[0]);
+        for c in 0..2 {
+            let mean = (0..40).map(|i| z[(i, c)]).sum::<f64>() / 40.0;
+            let squares = (0..40).map(|i| z[(i, c)].powi(2)).sum::<f64>();
+            assert!(mean.abs() < 1e-10);
+            assert!((squares - pca.eigenvalues()[c]).abs() < 1e-8 * pca.eigenvalues()[0]);
+        }
+
+        // The first component of a linear kernel is the principal axis `(2, 1) / sqrt(5)`.
+        let shifted = pca.transform(&[vec![3.0, 0.0], vec![1.0, -1.0]]).unwrap();
+        assert!(((shifted[(0, 0)] - shifted[(1, 0)]).abs() - 5f64.sqrt()).abs() < 1e-2);
+    }
+
+    #[test]
+    fn it_works2() {
+        let x = (0..30)
+            .map(|i| {
+                let t = 2.0 * PI * i as f64 / 30.0;
+                vec![t.cos(), t.sin()]
+            })
+            .collect::<Vec<Vec<f64>>>();
+        let params = [1.0, 0.5];
+
+        let pca = KernelPCA::fit(RBF, &params, x.clone(), 12).unwrap();
+        let point = vec![0.6f64.cos(), 0.6f64.sin()];
+        let z = pca.transform(std::slice::from_ref(&point)).unwrap();
+        let projection = (0..pca.components())
+            .map(|c| z[(0, c)])
+            .collect::<Vec<f64>>();
+
+        let pre_image = pca.pre_image(&projection, 100).unwrap();
+
+        assert!((pre_image[0] - point[0]).abs() < 0.05, "{:?}", pre_image);
+        assert!((pre_image[1] - point[1]).abs() < 0.05, "{:?}", pre_image);
+        match KernelPCA::fit(SpectralMixture::new(2, 1), &[1.0, 0.1, 0.1, 0.5, 0.5], x, 3)
+            .unwrap()
+            .pre_image(&[0.0; 3], 10)
+        {
+            Err(KernelError::InvalidArgument) => (),
+            _ => panic!(),
+        };
+    }
+}
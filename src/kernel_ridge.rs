@@ -0,0 +1,286 @@
+use crate::linalg::{cholesky, cholesky_inverse, multiply};
+use crate::{Adam, KernelError, ParamsDifferentiableKernel, PositiveDefiniteKernel, Value};
+use opensrdk_linear_algebra::Matrix;
+use rayon::prelude::*;
+
+/// Kernel ridge regression `f(x) = k(x, X) α` with `α = (K + λ I)^-1 y`.
+///
+/// The leave-one-out residuals come in closed form as `α_i / ((K + λ I)^-1)_ii`,
+/// and `search` tunes the params and `λ` by minimizing their mean square.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KernelRidge<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    kernel: K,
+    params: Vec<f64>,
+    lambda: f64,
+    x: Vec<T>,
+    alpha: Vec<f64>,
+    loo_residuals: Vec<f64>,
+}
+
+/// `(K + λ I)^-1` and `α`.
+fn solve<K, T>(
+    kernel: &K,
+    params: &[f64],
+    x: &[T],
+    y: &[f64],
+    lambda: f64,
+) -> Result<(Matrix, Vec<f64>), KernelError>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    if x.len() != y.len() || x.is_empty() {
+        return Err(KernelError::InvalidArgument);
+    }
+    if lambda.is_nan() || lambda <= 0.0 {
+        return Err(KernelError::InvalidParameter);
+    }
+
+    let mut c = kernel.gram_matrix(params, x)?;
+    for i in 0..x.len() {
+        c[(i, i)] += lambda;
+    }
+    let c_inv = cholesky_inverse(&cholesky(&c)?);
+    let alpha = (0..x.len())
+        .map(|i| (0..x.len()).map(|j| c_inv[(i, j)] * y[j]).sum())
+        .collect();
+
+    Ok((c_inv, alpha))
+}
+
+impl<K, T> KernelRidge<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    pub fn fit(
+        kernel: K,
+        params: &[f64],
+        x: Vec<T>,
+        y: &[f64],
+        lambda: f64,
+    ) -> Result<Self, KernelError> {
+        let (c_inv, alpha) = solve(&kernel, params, &x, y, lambda)?;
+        let loo_residuals = alpha
+            .iter()
+            .enumerate()
+            .map(|(i, alpha_i)| alpha_i / c_inv[(i, i)])
+            .collect();
+
+        Ok(Self {
+            kernel,
+            params: params.to_vec(),
+            lambda,
+            x,
+            alpha,
+            loo_residuals,
+        })
+    }
+
+    pub fn kernel_ref(&self) -> &K {
+        &self.kernel
+    }
+
+    pub fn params(&self) -> &[f64] {
+        &self.params
+    }
+
+    pub fn lambda(&self) -> f64 {
+        self.lambda
+    }
+
+    pub fn x(&self) -> &[T] {
+        &self.x
+    }
+
+    /// Dual coefficients `α`.
+    pub fn alpha(&self) -> &[f64] {
+        &self.alpha
+    }
+
+    pub fn predict(&self, xnew: &[T]) -> Result<Vec<f64>, KernelError> {
+        xnew.par_iter()
+            .map(|xi| {
+                self.x
+                    .iter()
+                    .zip(self.alpha.iter())
+                    .map(|(xj, alpha_j)| Ok(self.kernel.value(&self.params, xi, xj)? * alpha_j))
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// `y_i - f_-i(x_i)`, where `f_-i` is fitted without the `i`-th sample.
+    pub fn loo_residuals(&self) -> &[f64] {
+        &self.loo_residuals
+    }
+
+    /// Mean square of the leave-one-out residuals.
+    pub fn loo_error(&self) -> f64 {
+        self.loo_residuals.iter().map(|r| r.powi(2)).sum::<f64>() / self.loo_residuals.len() as f64
+    }
+}
+
+impl<K, T> KernelRidge<K, T>
+where
+    K: ParamsDifferentiableKernel<T>,
+    T: Value,
+{
+    /// Mean square of the leave-one-out residuals and its gradient with respect to `[params..., ln λ]`.
+    pub fn loo_error_gradient(
+        kernel: &K,
+        params: &[f64],
+        x: &[T],
+        y: &[f64],
+        lambda: f64,
+    ) -> Result<(f64, Vec<f64>), KernelError> {
+        let n = x.len();
+        let (c_inv, alpha) = solve(kernel, params, x, y, lambda)?;
+        let residuals = (0..n)
+            .map(|i| alpha[i] / c_inv[(i, i)])
+            .collect::<Vec<f64>>();
+        let error = residuals.iter().map(|r| r.powi(2)).sum::<f64>() / n as f64;
+
+        // For C = K + λ I, dα = -C^-1 dC α and d(C^-1)_ii = -(C^-1 dC C^-1)_ii.
+        let diff_along = |dc: &Matrix| {
+            let dc_alpha = (0..n)
+                .map(|i| (0..n).map(|j| dc[(i, j)] * alpha[j]).sum::<f64>())
+                .collect::<Vec<f64>>();
+            let dc_c_inv = multiply(dc, &c_inv);
+
+            (0..n)
+                .map(|i| {
+                    let diff_alpha = -(0..n).map(|j| c_inv[(i, j)] * dc_alpha[j]).sum::<f64>();
+                    let diff_c_inv = -(0..n)
+                        .map(|j| c_inv[(i, j)] * dc_c_inv[(j, i)])
+                        .sum::<f64>();
+                    let diff_residual =
+                        diff_alpha / c_inv[(i, i)] - alpha[i] * diff_c_inv / c_inv[(i, i)].powi(2);
+                    2.0 * residuals[i] * diff_residual
+                })
+                .sum::<f64>()
+                / n as f64
+        };
+
+        let mut dc = kernel.gram_matrix_diff_params(params, x)?;
+        let mut diff_lambda = Matrix::new(n, n);
+        for i in 0..n {
+            diff_lambda[(i, i)] = lambda;
+        }
+        dc.push(diff_lambda);
+        let gradient = dc.par_iter().map(diff_along).collect();
+
+        Ok((error, gradient))
+    }
+
+    /// Fits with the params and `λ` that minimize the leave-one-out error by `Adam`, starting from the given ones.
+    pub fn search(
+        kernel: K,
+        params: &[f64],
+        x: Vec<T>,
+        y: &[f64],
+        lambda: f64,
+        adam: &Adam,
+    ) -> Result<Self, KernelError> {
+        let (params, lambda) = adam.minimize_with_log_scale(params, lambda, |params, lambda| {
+            Self::loo_error_gradient(&kernel, params, &x, y, lambda)
+        })?;
+
+        Self::fit(kernel, &params, x, y, lambda)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::prelude::*;
+
+    #[test]
+    fn it_works() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let x = (0..30)
+            .map(|_| vec![rng.gen_range(-2.0..2.0)])
+            .collect::<Vec<Vec<f64>>>();
+        let y = x
+            .iter()
+            .map(|xi| xi[0].sin() + 0.1 * rng.gen_range(-1.0..1.0))
+            .collect::<Vec<f64>>();
+        let params = [1.0, 0.5];
+
+        let ridge = KernelRidge::fit(RBF, &params, x.clone(), &y, 0.1).unwrap();
+
+        for i in [0, 7, 19] {
+            let mut x_rest = x.clone();
+            let mut y_rest = y.clone();
+            let x_i = x_rest.remove(i);
+            let y_i = y_rest.remove(i);
+            let without = KernelRidge::fit(RBF, &params, x_rest, &y_rest, 0.1).unwrap();
+            let expected = y_i - without.predict(&[x_i]).unwrap()[0];
+
+            assert!((ridge.loo_residuals()[i] - expected).abs() < 1e-8);
+        }
+
+        let prediction = ridge.predict(&[vec![0.5]]).unwrap();
+        assert!((prediction[0] - 0.5f64.sin()).abs() < 0.1);
+    }
+
+    #[test]
+    fn check_gradients() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let x = (0..20)
+            .map(|_| vec![rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0)])
+            .collect::<Vec<Vec<f64>>>();
+        let y = x.iter().map(|xi| xi[0] * xi[1]).collect::<Vec<f64>>();
+        let kernel = RBF + Periodic;
+        let params = [1.0, 0.8, 0.5, 1.5];
+        let lambda = 0.05;
+
+        let (error, gradient) =
+            KernelRidge::loo_error_gradient(&kernel, &params, &x, &y, lambda).unwrap();
+        assert_log_scale_gradient(&gradient, &params, lambda, |params, lambda| {
+            KernelRidge::fit(kernel.clone(), params, x.clone(), &y, lambda)
+                .unwrap()
+                .loo_error()
+        });
+        let expected = KernelRidge::fit(kernel, &params, x, &y, lambda)
+            .unwrap()
+            .loo_error();
+        assert!((error - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn it_works2() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let x = (0..25)
+            .map(|_| {
+                Sequence(
+                    (0..3)
+                        .map(|_| vec![rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)])
+                        .collect(),
+                )
+            })
+            .collect::<Vec<Sequence>>();
+        let y = x
+            .iter()
+            .map(|p| p.0.iter().map(|v| (v[0] + v[1]).cos()).sum())
+            .collect::<Vec<f64>>();
+        let kernel = Transformed::new(
+            Convolutional::new(RBF),
+            vec![ParamsTransform::Exp, ParamsTransform::Exp],
+        )
+        .unwrap();
+        let params = [0.0, 0.0];
+
+        let initial = KernelRidge::fit(kernel.clone(), &params, x.clone(), &y, 1.0).unwrap();
+        let searched =
+            KernelRidge::search(kernel, &params, x.clone(), &y, 1.0, &Adam::new(0.1, 50)).unwrap();
+
+        assert!(searched.loo_error() < initial.loo_error());
+        assert_eq!(searched.predict(&x[0..2]).unwrap().len(), 2);
+    }
+}
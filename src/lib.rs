@@ -1,39 +1,84 @@
 pub extern crate opensrdk_linear_algebra;
+pub extern crate rand;
+extern crate rand_distr;
 extern crate rayon;
 extern crate thiserror;
 
+pub use active_dims::*;
 pub use add::*;
+pub use additive::*;
 pub use ard::*;
+pub use boxed::*;
 pub use constant::*;
 pub use convolutional::*;
+pub use coregionalization::*;
 pub use exponential::*;
+pub use expression::*;
+pub use gaussian_process::*;
+pub use gradient_check::*;
+pub use hsic::*;
 pub use instant::*;
+pub use kernel_pca::*;
+pub use kernel_ridge::*;
 pub use linear::*;
+pub use matern::*;
+pub use mmd::*;
 pub use mul::*;
-pub use neural_network::{deep_neural_network::*, relu::*};
+pub use neural_network::{
+    arc_cosine::*, deep_neural_network::*, erf::*, gauss_hermite::*, neural_tangent::*, relu::*,
+    step::*, Activation, ActivationFunction,
+};
+pub use nystrom::*;
+pub use optimizer::*;
 pub use periodic::*;
+pub use random_fourier_features::*;
+pub use rational_quadratic::*;
 pub use rbf::*;
 pub use spectral_mixture::*;
-pub use traits::{
-    params_differentiable::*, params_differentiable::*, value_differentiable::*,
-    value_differentiable::*,
-};
+pub use svm::{svc::*, svr::*, SmoOptions};
+pub use traits::*;
+pub use transformed::*;
+pub use with_params::*;
 
+use opensrdk_linear_algebra::{DiagonalMatrix, Matrix};
+use rayon::prelude::*;
 use std::fmt::Debug;
 
+pub mod active_dims;
 pub mod add;
+pub mod additive;
 pub mod ard;
+pub mod boxed;
 pub mod constant;
 pub mod convolutional;
+pub mod coregionalization;
 pub mod exponential;
+pub mod expression;
+pub mod gaussian_process;
+pub mod gradient_check;
+pub mod hsic;
 pub mod instant;
+pub mod kernel_pca;
+pub mod kernel_ridge;
 pub mod linear;
+pub mod matern;
+pub mod mmd;
 pub mod mul;
 pub mod neural_network;
+pub mod nystrom;
+pub mod optimizer;
 pub mod periodic;
+pub mod random_fourier_features;
+pub mod rational_quadratic;
 pub mod rbf;
 pub mod spectral_mixture;
+pub mod svm;
 pub mod traits;
+pub mod transformed;
+pub mod with_params;
+
+mod linalg;
+mod special;
 
 pub trait Value: Clone + Debug + Send + Sync {}
 impl<T> Value for T where T: Clone + Debug + Send + Sync {}
@@ -45,6 +90,92 @@ where
     fn params_len(&self) -> usize;
 
     fn value(&self, params: &[f64], x: &T, xprime: &T) -> Result<f64, KernelError>;
+
+    /// Names of the params in the order `value` consumes them.
+    /// Composite kernels prefix the names of their children with their path, e.g. `add.lhs.rbf.lengthscale`.
+    fn params_names(&self) -> Vec<String> {
+        (0..self.params_len())
+            .map(|i| format!("param{}", i))
+            .collect()
+    }
+
+    fn params_index(&self, name: &str) -> Result<usize, KernelError> {
+        self.params_names()
+            .iter()
+            .position(|n| n == name)
+            .ok_or_else(|| KernelError::ParameterNotFound(name.to_owned()))
+    }
+
+    fn get_param(&self, params: &[f64], name: &str) -> Result<f64, KernelError> {
+        if params.len() != self.params_len() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+
+        Ok(params[self.params_index(name)?])
+    }
+
+    fn set_param(&self, params: &mut [f64], name: &str, value: f64) -> Result<(), KernelError> {
+        if params.len() != self.params_len() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+
+        params[self.params_index(name)?] = value;
+
+        Ok(())
+    }
+
+    /// Gram matrix `K(X, X)`. Only the upper triangle is evaluated and mirrored.
+    fn gram_matrix(&self, params: &[f64], x: &[T]) -> Result<Matrix, KernelError> {
+        let n = x.len();
+        let upper = (0..n)
+            .into_par_iter()
+            .map(|j| {
+                (0..=j)
+                    .map(|i| self.value(params, &x[i], &x[j]))
+                    .collect::<Result<Vec<f64>, KernelError>>()
+            })
+            .collect::<Result<Vec<Vec<f64>>, KernelError>>()?;
+
+        let mut k = Matrix::new(n, n);
+        for (j, column) in upper.into_iter().enumerate() {
+            for (i, kij) in column.into_iter().enumerate() {
+                k[(i, j)] = kij;
+                k[(j, i)] = kij;
+            }
+        }
+
+        Ok(k)
+    }
+
+    /// Cross-covariance matrix `K(X, X')` with `x.len()` rows and `xprime.len()` columns.
+    fn cross_covariance_matrix(
+        &self,
+        params: &[f64],
+        x: &[T],
+        xprime: &[T],
+    ) -> Result<Matrix, KernelError> {
+        let n = x.len();
+        let mut k = Matrix::new(n, xprime.len());
+        k.elems_mut()
+            .par_iter_mut()
+            .enumerate()
+            .try_for_each(|(index, kij)| {
+                *kij = self.value(params, &x[index % n], &xprime[index / n])?;
+                Ok(())
+            })?;
+
+        Ok(k)
+    }
+
+    /// Diagonal of the Gram matrix `K(X, X)`.
+    fn gram_diagonal(&self, params: &[f64], x: &[T]) -> Result<DiagonalMatrix, KernelError> {
+        let d = x
+            .par_iter()
+            .map(|xi| self.value(params, xi, xi))
+            .collect::<Result<Vec<f64>, KernelError>>()?;
+
+        Ok(DiagonalMatrix::new(d))
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -55,6 +186,16 @@ pub enum KernelError {
     InvalidParameter,
     #[error("invalid argument")]
     InvalidArgument,
+    #[error("parameter not found: {0}")]
+    ParameterNotFound(String),
+    #[error("invalid kernel expression: {0}")]
+    InvalidExpression(String),
+    #[error("kernel is not differentiable")]
+    NotDifferentiable,
+    #[error("matrix is not positive definite")]
+    NotPositiveDefinite,
+    #[error("eigendecomposition did not converge")]
+    NotConverged,
 }
 
 #[cfg(test)]
@@ -73,4 +214,78 @@ mod tests {
 
         println!("{}", test_value);
     }
+
+    #[test]
+    fn gram_matrix() {
+        let kernel = RBF + Constant;
+        let params = [1.0, 2.0, 0.5];
+        let x = vec![vec![0.0, 1.0], vec![1.0, 1.0], vec![3.0, -1.0]];
+        let xprime = vec![vec![2.0, 0.0], vec![0.0, 0.0]];
+
+        let k = kernel.gram_matrix(&params, &x).unwrap();
+        let kxxprime = kernel
+            .cross_covariance_matrix(&params, &x, &xprime)
+            .unwrap();
+        let kdiag = kernel.gram_diagonal(&params, &x).unwrap();
+
+        assert_eq!((k.rows(), k.cols()), (3, 3));
+        assert_eq!((kxxprime.rows(), kxxprime.cols()), (3, 2));
+        for i in 0..3 {
+            assert_eq!(kdiag.d()[i], kernel.value(&params, &x[i], &x[i]).unwrap());
+            for j in 0..3 {
+                assert_eq!(k[(i, j)], kernel.value(&params, &x[i], &x[j]).unwrap());
+            }
+            for j in 0..2 {
+                assert_eq!(
+                    kxxprime[(i, j)],
+                    kernel.value(&params, &x[i], &xprime[j]).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn gram_matrix_error() {
+        let kernel = ARD(2);
+        let x = vec![vec![0.0, 1.0], vec![1.0, 1.0, 1.0]];
+
+        match kernel.gram_matrix(&[1.0, 1.0], &x) {
+            Err(KernelError::InvalidArgument) => (),
+            _ => panic!(),
+        };
+        match kernel.cross_covariance_matrix(&[1.0], &x, &x) {
+            Err(KernelError::ParametersLengthMismatch) => (),
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn params_names() {
+        let kernel = RBF + Constant * Linear + Constant * Periodic + Constant * ARD(3);
+        let names = kernel.params_names();
+
+        assert_eq!(names.len(), kernel.params_len());
+        assert_eq!(names[0], "add.lhs.add.lhs.add.lhs.rbf.variance");
+        assert_eq!(names[1], "add.lhs.add.lhs.add.lhs.rbf.lengthscale");
+        assert_eq!(names[2], "add.lhs.add.lhs.add.rhs.mul.lhs.constant.value");
+        assert_eq!(names[4], "add.lhs.add.rhs.mul.rhs.periodic.amplitude");
+        assert_eq!(names[9], "add.rhs.mul.rhs.ard.relevance.2");
+
+        let mut params = vec![1.0; kernel.params_len()];
+        kernel
+            .set_param(&mut params, "add.lhs.add.rhs.mul.rhs.periodic.period", 3.0)
+            .unwrap();
+
+        assert_eq!(params[5], 3.0);
+        assert_eq!(
+            kernel
+                .get_param(&params, "add.lhs.add.rhs.mul.rhs.periodic.period")
+                .unwrap(),
+            3.0
+        );
+        match kernel.get_param(&params, "rbf.lengthscale") {
+            Err(KernelError::ParameterNotFound(name)) => assert_eq!(name, "rbf.lengthscale"),
+            _ => panic!(),
+        };
+    }
 }
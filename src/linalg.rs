@@ -0,0 +1,281 @@
+use crate::KernelError;
+use opensrdk_linear_algebra::Matrix;
+use rayon::prelude::*;
+
+const MAX_SWEEPS: usize = 100;
+
+/// Eigendecomposition `a = v diag(e) v^T` of a symmetric matrix.
+/// The eigenvalues are in descending order and the eigenvectors are the columns of `v`.
+///
+/// With the `lapack` feature this is `sytrd` followed by `stev` of opensrdk-linear-algebra,
+/// otherwise the cyclic Jacobi method.
+pub(crate) fn symmetric_eigen(a: &Matrix) -> Result<(Vec<f64>, Matrix), KernelError> {
+    // `stev` does not handle matrices smaller than 2x2, which are already diagonal anyway.
+    #[cfg(feature = "lapack")]
+    if a.rows() >= 2 {
+        return lapack_symmetric_eigen(a);
+    }
+
+    jacobi_symmetric_eigen(a)
+}
+
+#[cfg(feature = "lapack")]
+fn lapack_symmetric_eigen(a: &Matrix) -> Result<(Vec<f64>, Matrix), KernelError> {
+    // `dstev` fails only when the QL iteration does not converge, given a square input.
+    let (q, t) = a
+        .clone()
+        .sytrd()
+        .and_then(|sytrd| sytrd.orgtr())
+        .map_err(|_| KernelError::NotConverged)?;
+    let (e, z) = t.stev().map_err(|_| KernelError::NotConverged)?;
+    let v = multiply(&q, &z);
+
+    let n = e.len();
+    let mut reversed = Matrix::new(n, n);
+    for j in 0..n {
+        for k in 0..n {
+            reversed[(k, j)] = v[(k, n - 1 - j)];
+        }
+    }
+
+    Ok((e.into_iter().rev().collect(), reversed))
+}
+
+/// Fails with `NotConverged` when the off-diagonal part is still above the tolerance after `MAX_SWEEPS` sweeps.
+fn jacobi_symmetric_eigen(a: &Matrix) -> Result<(Vec<f64>, Matrix), KernelError> {
+    let n = a.rows();
+    let mut a = a.clone();
+    let mut v = Matrix::new(n, n);
+    for i in 0..n {
+        v[(i, i)] = 1.0;
+    }
+
+    for sweep in 0..=MAX_SWEEPS {
+        let off = (0..n)
+            .flat_map(|j| (0..j).map(move |i| (i, j)))
+            .map(|(i, j)| a[(i, j)].powi(2))
+            .sum::<f64>();
+        let scale = (0..n).map(|i| a[(i, i)].powi(2)).sum::<f64>();
+        if off <= f64::EPSILON.powi(2) * scale || off == 0.0 {
+            break;
+        }
+        if sweep == MAX_SWEEPS {
+            return Err(KernelError::NotConverged);
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[(p, q)] == 0.0 {
+                    continue;
+                }
+
+                let theta = (a[(q, q)] - a[(p, p)]) / (2.0 * a[(p, q)]);
+                let t = theta.signum() / (theta.abs() + (theta.powi(2) + 1.0).sqrt());
+                let c = 1.0 / (t.powi(2) + 1.0).sqrt();
+                let s = t * c;
+
+                for k in 0..n {
+                    let (akp, akq) = (a[(k, p)], a[(k, q)]);
+                    a[(k, p)] = c * akp - s * akq;
+                    a[(k, q)] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a[(p, k)], a[(q, k)]);
+                    a[(p, k)] = c * apk - s * aqk;
+                    a[(q, k)] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (v[(k, p)], v[(k, q)]);
+                    v[(k, p)] = c * vkp - s * vkq;
+                    v[(k, q)] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut order = (0..n).collect::<Vec<usize>>();
+    order.sort_by(|&i, &j| a[(j, j)].total_cmp(&a[(i, i)]));
+
+    let e = order.iter().map(|&i| a[(i, i)]).collect();
+    let mut sorted = Matrix::new(n, n);
+    for (j, &i) in order.iter().enumerate() {
+        for k in 0..n {
+            sorted[(k, j)] = v[(k, i)];
+        }
+    }
+
+    Ok((e, sorted))
+}
+
+/// Lower triangular `l` with `a = l l^T`.
+pub(crate) fn cholesky(a: &Matrix) -> Result<Matrix, KernelError> {
+    let n = a.rows();
+    if a.cols() != n {
+        return Err(KernelError::InvalidArgument);
+    }
+
+    let mut l: Matrix = Matrix::new(n, n);
+    for j in 0..n {
+        let d = a[(j, j)] - (0..j).map(|k| l[(j, k)].powi(2)).sum::<f64>();
+        if d.is_nan() || d <= 0.0 {
+            return Err(KernelError::NotPositiveDefinite);
+        }
+        l[(j, j)] = d.sqrt();
+
+        for i in j + 1..n {
+            let s = a[(i, j)] - (0..j).map(|k| l[(i, k)] * l[(j, k)]).sum::<f64>();
+            l[(i, j)] = s / l[(j, j)];
+        }
+    }
+
+    Ok(l)
+}
+
+/// `l^-1 b` for a lower triangular `l`.
+pub(crate) fn forward_substitution(l: &Matrix, b: &[f64]) -> Vec<f64> {
+    let mut x = b.to_vec();
+    for i in 0..l.rows() {
+        x[i] = (x[i] - (0..i).map(|k| l[(i, k)] * x[k]).sum::<f64>()) / l[(i, i)];
+    }
+
+    x
+}
+
+/// `(l l^T)^-1 b` by forward and back substitution.
+pub(crate) fn cholesky_solve(l: &Matrix, b: &[f64]) -> Vec<f64> {
+    let n = l.rows();
+    let mut x = forward_substitution(l, b);
+    for i in (0..n).rev() {
+        x[i] = (x[i] - (i + 1..n).map(|k| l[(k, i)] * x[k]).sum::<f64>()) / l[(i, i)];
+    }
+
+    x
+}
+
+/// `(l l^T)^-1`
+pub(crate) fn cholesky_inverse(l: &Matrix) -> Matrix {
+    let n = l.rows();
+    let mut inverse = Matrix::new(n, n);
+    let mut e = vec![0.0; n];
+    for j in 0..n {
+        e[j] = 1.0;
+        for (i, x) in cholesky_solve(l, &e).into_iter().enumerate() {
+            inverse[(i, j)] = x;
+        }
+        e[j] = 0.0;
+    }
+
+    inverse
+}
+
+/// `a b`
+pub(crate) fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut c = Matrix::new(a.rows(), b.cols());
+    c.elems_mut()
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(index, cij)| {
+            let (i, j) = (index % a.rows(), index / a.rows());
+            *cij = (0..a.cols()).map(|k| a[(i, k)] * b[(k, j)]).sum();
+        });
+
+    c
+}
+
+/// `Σ_i a_ij / n` for each `j`.
+pub(crate) fn column_means(a: &Matrix) -> Vec<f64> {
+    (0..a.cols())
+        .map(|j| (0..a.rows()).map(|i| a[(i, j)]).sum::<f64>() / a.rows() as f64)
+        .collect()
+}
+
+/// `H k H` with `H = I - 1/n` for a symmetric `k`, the Gram matrix centred in the feature space.
+pub(crate) fn centre(k: &Matrix) -> Matrix {
+    let n = k.rows();
+    let column_means = column_means(k);
+    let mean = column_means.iter().sum::<f64>() / n as f64;
+    let mut kc = k.clone();
+    kc.elems_mut()
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(index, kij)| {
+            *kij += mean - column_means[index % n] - column_means[index / n];
+        });
+
+    kc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let mut a = Matrix::new(3, 3);
+        a.elems_mut()
+            .copy_from_slice(&[4.0, 1.0, 2.0, 1.0, 3.0, 0.5, 2.0, 0.5, 5.0]);
+
+        let (e, v) = symmetric_eigen(&a).unwrap();
+
+        assert!(e[0] >= e[1] && e[1] >= e[2]);
+        for i in 0..3 {
+            for j in 0..3 {
+                let reconstructed = (0..3).map(|k| v[(i, k)] * e[k] * v[(j, k)]).sum::<f64>();
+                assert!((reconstructed - a[(i, j)]).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn it_works2() {
+        let mut a = Matrix::new(3, 3);
+        a.elems_mut()
+            .copy_from_slice(&[4.0, 1.0, 2.0, 1.0, 3.0, 0.5, 2.0, 0.5, 5.0]);
+
+        let l = cholesky(&a).unwrap();
+        let x = cholesky_solve(&l, &[1.0, 2.0, 3.0]);
+        let inverse = multiply(&cholesky_inverse(&l), &a);
+
+        for i in 0..3 {
+            let ax = (0..3).map(|j| a[(i, j)] * x[j]).sum::<f64>();
+            assert!((ax - (i + 1) as f64).abs() < 1e-10);
+            for j in 0..3 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((inverse[(i, j)] - expected).abs() < 1e-10);
+            }
+        }
+        a[(0, 0)] = -1.0;
+        match cholesky(&a) {
+            Err(KernelError::NotPositiveDefinite) => (),
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn it_works3() {
+        let mut a = Matrix::new(3, 3);
+        a.elems_mut()
+            .copy_from_slice(&[4.0, 1.0, 2.0, 1.0, 3.0, 0.5, 2.0, 0.5, 5.0]);
+
+        let centred = centre(&a);
+
+        for i in 0..3 {
+            assert!((0..3).map(|j| centred[(i, j)]).sum::<f64>().abs() < 1e-12);
+            assert!((0..3).map(|j| centred[(j, i)]).sum::<f64>().abs() < 1e-12);
+        }
+        assert!((centred[(0, 1)] - centred[(1, 0)]).abs() < 1e-12);
+        assert!((centred[(0, 0)] - (4.0 - 2.0 * 7.0 / 3.0 + 19.0 / 9.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn it_works4() {
+        let mut a = Matrix::new(2, 2);
+        a.elems_mut()
+            .copy_from_slice(&[1.0, f64::NAN, f64::NAN, 1.0]);
+
+        match jacobi_symmetric_eigen(&a) {
+            Err(KernelError::NotConverged) => (),
+            _ => panic!(),
+        };
+    }
+}
@@ -4,24 +4,36 @@ use crate::{
 };
 use opensrdk_linear_algebra::*;
 use rayon::prelude::*;
+use std::fmt::{self, Display, Formatter};
 use std::{ops::Add, ops::Mul};
 
 const PARAMS_LEN: usize = 0;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Linear;
 
+impl Display for Linear {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Linear")
+    }
+}
+
 impl PositiveDefiniteKernel<Vec<f64>> for Linear {
     fn params_len(&self) -> usize {
         PARAMS_LEN
     }
 
+    fn params_names(&self) -> Vec<String> {
+        vec![]
+    }
+
     fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
         if params.len() != PARAMS_LEN {
-            return Err(KernelError::ParametersLengthMismatch.into());
+            return Err(KernelError::ParametersLengthMismatch);
         }
         if x.len() != xprime.len() {
-            return Err(KernelError::InvalidArgument.into());
+            return Err(KernelError::InvalidArgument);
         }
 
         let fx = x
@@ -63,8 +75,8 @@ impl ValueDifferentiableKernel<Vec<f64>> for Linear {
         x: &Vec<f64>,
         xprime: &Vec<f64>,
     ) -> Result<Vec<f64>, KernelError> {
-        let value = &self.value(params, x, xprime)?;
-        let diff = (2.0 / value * x.clone().col_mat()).vec();
+        let value = self.value(params, x, xprime)?;
+        let diff = (1.0 / value * xprime.clone().col_mat()).vec();
         Ok(diff)
     }
 }
@@ -94,4 +106,9 @@ mod tests {
 
         assert_eq!(test_value, 10.0);
     }
+
+    #[test]
+    fn check_gradients() {
+        assert_gradients(&Linear, &[], 3);
+    }
 }
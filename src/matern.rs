@@ -0,0 +1,293 @@
+use super::PositiveDefiniteKernel;
+use crate::special::{ln_bessel_k, ln_gamma};
+use crate::traits::stationary::{sample_student_t, student_t_density};
+use crate::{
+    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, StationaryKernel,
+    ValueDifferentiableKernel,
+};
+use rand::Rng;
+use rayon::prelude::*;
+use std::fmt::{self, Display, Formatter};
+use std::{ops::Add, ops::Mul};
+
+const PARAMS_LEN: usize = 2;
+
+/// Matérn kernel of smoothness `nu` with params `[variance, lengthscale]`.
+///
+/// `nu = 0.5, 1.5, 2.5` use their closed forms, any other positive `nu` the modified Bessel function.
+/// http://www.gaussianprocess.org/gpml/chapters/RW4.pdf
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Matern {
+    nu: f64,
+}
+
+impl Matern {
+    pub fn new(nu: f64) -> Self {
+        Self { nu }
+    }
+
+    pub fn nu(&self) -> f64 {
+        self.nu
+    }
+
+    fn norm(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
+        if params.len() != PARAMS_LEN {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+        if x.len() != xprime.len() {
+            return Err(KernelError::InvalidArgument);
+        }
+        if self.nu.is_nan() || self.nu <= 0.0 {
+            return Err(KernelError::InvalidParameter);
+        }
+        if !params[1].is_finite() || params[1] <= 0.0 {
+            return Err(KernelError::InvalidParameter);
+        }
+
+        let v = x
+            .par_iter()
+            .zip(xprime.par_iter())
+            .map(|(x_i, xprime_i)| (x_i - xprime_i).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        if v.is_nan() {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        Ok(v)
+    }
+
+    /// `ln(k / variance)` and its derivative with respect to `norm`.
+    fn ln_correlation(&self, lengthscale: f64, norm: f64) -> (f64, f64) {
+        if norm == 0.0 {
+            return (0.0, 0.0);
+        }
+
+        if self.nu == 0.5 {
+            (-norm / lengthscale, -1.0 / lengthscale)
+        } else if self.nu == 1.5 {
+            let a = 3f64.sqrt() * norm / lengthscale;
+            let diff_a = -a / (1.0 + a);
+            ((1.0 + a).ln() - a, diff_a * a / norm)
+        } else if self.nu == 2.5 {
+            let a = 5f64.sqrt() * norm / lengthscale;
+            let diff_a = -a * (1.0 + a) / (3.0 + 3.0 * a + a.powi(2));
+            ((1.0 + a + a.powi(2) / 3.0).ln() - a, diff_a * a / norm)
+        } else {
+            self.ln_correlation_bessel(lengthscale, norm)
+        }
+    }
+
+    fn ln_correlation_bessel(&self, lengthscale: f64, norm: f64) -> (f64, f64) {
+        let nu = self.nu;
+        let z = (2.0 * nu).sqrt() * norm / lengthscale;
+        let ln_k_nu = ln_bessel_k(nu, z);
+
+        let ln_correlation = (1.0 - nu) * 2f64.ln() - ln_gamma(nu) + nu * z.ln() + ln_k_nu;
+        // d/dz (z^ν K_ν(z)) = -z^ν K_{ν-1}(z)
+        let diff_z = -(ln_bessel_k(nu - 1.0, z) - ln_k_nu).exp();
+
+        (ln_correlation, diff_z * z / norm)
+    }
+}
+
+impl Display for Matern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Matern({})", self.nu)
+    }
+}
+
+impl PositiveDefiniteKernel<Vec<f64>> for Matern {
+    fn params_len(&self) -> usize {
+        PARAMS_LEN
+    }
+
+    fn params_names(&self) -> Vec<String> {
+        vec![
+            "matern.variance".to_owned(),
+            "matern.lengthscale".to_owned(),
+        ]
+    }
+
+    fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
+        let norm = self.norm(params, x, xprime)?;
+
+        let fx = params[0] * self.ln_correlation(params[1], norm).0.exp();
+
+        Ok(fx)
+    }
+}
+
+impl<R> Add<R> for Matern
+where
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelAdd<Self, R, Vec<f64>>;
+
+    fn add(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<R> Mul<R> for Matern
+where
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelMul<Self, R, Vec<f64>>;
+
+    fn mul(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl ValueDifferentiableKernel<Vec<f64>> for Matern {
+    fn ln_diff_value(
+        &self,
+        params: &[f64],
+        x: &Vec<f64>,
+        xprime: &Vec<f64>,
+    ) -> Result<Vec<f64>, KernelError> {
+        let norm = self.norm(params, x, xprime)?;
+        if norm == 0.0 {
+            return Ok(vec![0.0; x.len()]);
+        }
+
+        let diff_norm = self.ln_correlation(params[1], norm).1;
+        let diff = x
+            .par_iter()
+            .zip(xprime.par_iter())
+            .map(|(x_i, xprime_i)| diff_norm * (x_i - xprime_i) / norm)
+            .collect::<Vec<f64>>();
+
+        Ok(diff)
+    }
+}
+
+impl ParamsDifferentiableKernel<Vec<f64>> for Matern {
+    fn ln_diff_params(
+        &self,
+        params: &[f64],
+        x: &Vec<f64>,
+        xprime: &Vec<f64>,
+    ) -> Result<Vec<f64>, KernelError> {
+        let norm = self.norm(params, x, xprime)?;
+        let diff_norm = self.ln_correlation(params[1], norm).1;
+
+        let diff0 = 1.0 / params[0];
+        let diff1 = -diff_norm * norm / params[1];
+        let diff = vec![diff0, diff1];
+
+        Ok(diff)
+    }
+}
+
+impl StationaryKernel for Matern {
+    fn variance(&self, params: &[f64]) -> Result<f64, KernelError> {
+        if params.len() != PARAMS_LEN {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+        if self.nu.is_nan() || self.nu <= 0.0 {
+            return Err(KernelError::InvalidParameter);
+        }
+
+        Ok(params[0])
+    }
+
+    /// Multivariate Student's t distribution of `2ν` degrees of freedom and scale `1 / lengthscale`,
+    /// scaled by the variance.
+    fn spectral_density(&self, params: &[f64], omega: &[f64]) -> Result<f64, KernelError> {
+        let variance = self.variance(params)?;
+
+        Ok(variance * student_t_density(2.0 * self.nu, params[1], omega))
+    }
+
+    fn sample_frequency<R>(
+        &self,
+        params: &[f64],
+        dims: usize,
+        rng: &mut R,
+    ) -> Result<Vec<f64>, KernelError>
+    where
+        R: Rng + ?Sized,
+    {
+        self.variance(params)?;
+
+        sample_student_t(2.0 * self.nu, params[1], dims, rng)
+    }
+
+    fn is_isotropic(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let kernel = Matern::new(0.5);
+
+        let test_value = kernel
+            .value(&[1.0, 1.0], &vec![1.0, 0.0, 0.0], &vec![0.0, 0.0, 0.0])
+            .unwrap();
+
+        assert_eq!(
+            test_value,
+            Exponential
+                .value(&[1.0], &vec![1.0, 0.0, 0.0], &vec![0.0, 0.0, 0.0])
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn it_works2() {
+        for &nu in [0.5, 1.5, 2.5].iter() {
+            let kernel = Matern::new(nu);
+            for &norm in [1e-3, 0.4, 1.0, 3.7].iter() {
+                let closed = kernel.ln_correlation(0.8, norm);
+                let bessel = kernel.ln_correlation_bessel(0.8, norm);
+
+                assert!((closed.0 - bessel.0).abs() < 1e-10);
+                assert!((closed.1 - bessel.1).abs() < 1e-8);
+            }
+        }
+    }
+
+    #[test]
+    fn it_works3() {
+        let kernel = Matern::new(1.5);
+
+        let test_value = kernel
+            .value(&[2.0, 1.0], &vec![0.0, 1.0], &vec![0.0, 1.0])
+            .unwrap();
+
+        assert_eq!(test_value, 2.0);
+        match kernel.value(&[1.0], &vec![0.0], &vec![0.0]) {
+            Err(KernelError::ParametersLengthMismatch) => (),
+            _ => panic!(),
+        };
+        match Matern::new(-1.0).value(&[1.0, 1.0], &vec![0.0], &vec![0.0]) {
+            Err(KernelError::InvalidParameter) => (),
+            _ => panic!(),
+        };
+        for &lengthscale in [-1.0, 0.0, f64::NAN].iter() {
+            match Matern::new(1.2).value(&[1.0, lengthscale], &vec![0.0], &vec![1.0]) {
+                Err(KernelError::InvalidParameter) => (),
+                _ => panic!(),
+            };
+        }
+        match Matern::new(1.2).value(&[1.0, 1.0], &vec![f64::NAN], &vec![1.0]) {
+            Err(KernelError::InvalidArgument) => (),
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn check_gradients() {
+        for &nu in [0.5, 0.8, 1.5, 2.5, 3.3].iter() {
+            assert_gradients(&Matern::new(nu), &[1.5, 0.8], 3);
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use crate::linalg::{centre, symmetric_eigen};
+use crate::{KernelError, PositiveDefiniteKernel, Value};
+use opensrdk_linear_algebra::Matrix;
+use rand::prelude::*;
+use rand_distr::StandardNormal;
+use rayon::prelude::*;
+
+/// Kernel two-sample test with the maximum mean discrepancy between samples `x ~ P` and `y ~ Q`,
+/// `MMD² = E[k(x, x')] + E[k(y, y')] - 2 E[k(x, y)]`, which is zero if and only if `P = Q` for characteristic kernels.
+///
+/// The Gram matrix of the pooled sample and the eigenvalues of its centred form are computed once in `new`,
+/// and shared by the statistic and the null approximations.
+/// https://www.jmlr.org/papers/volume13/gretton12a/gretton12a.pdf
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MaximumMeanDiscrepancy {
+    /// Gram matrix of `[x..., y...]`.
+    k: Matrix,
+    n: usize,
+    mmd2: f64,
+    /// Positive eigenvalues of the centred `k` divided by `N = n + m`.
+    lambda: Vec<f64>,
+}
+
+/// Unbiased `MMD²` of the pooled Gram matrix, where `index[..n]` are the points of `x` and `index[n..]` those of `y`.
+fn unbiased(k: &Matrix, index: &[usize], n: usize) -> f64 {
+    let m = index.len() - n;
+    let (sum_xx, sum_yy, sum_xy) = (0..index.len())
+        .into_par_iter()
+        .map(|i| {
+            let mut sums = (0.0, 0.0, 0.0);
+            for j in 0..index.len() {
+                if i == j {
+                    continue;
+                }
+                let kij = k[(index[i], index[j])];
+                match (i < n, j < n) {
+                    (true, true) => sums.0 += kij,
+                    (false, false) => sums.1 += kij,
+                    (true, false) => sums.2 += kij,
+                    (false, true) => (),
+                }
+            }
+            sums
+        })
+        .reduce(|| (0.0, 0.0, 0.0), |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2));
+
+    sum_xx / (n * (n - 1)) as f64 + sum_yy / (m * (m - 1)) as f64 - 2.0 * sum_xy / (n * m) as f64
+}
+
+impl MaximumMeanDiscrepancy {
+    /// Both samples need at least 2 points.
+    pub fn new<K, T>(kernel: &K, params: &[f64], x: &[T], y: &[T]) -> Result<Self, KernelError>
+    where
+        K: PositiveDefiniteKernel<T>,
+        T: Value,
+    {
+        if x.len() < 2 || y.len() < 2 {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let pooled = x.iter().chain(y.iter()).cloned().collect::<Vec<T>>();
+        let k = kernel.gram_matrix(params, &pooled)?;
+        let index = (0..pooled.len()).collect::<Vec<usize>>();
+        let mmd2 = unbiased(&k, &index, x.len());
+
+        let (e, _) = symmetric_eigen(&centre(&k))?;
+        let lambda = e
+            .into_iter()
+            .take_while(|&e| e > 0.0)
+            .map(|e| e / pooled.len() as f64)
+            .collect();
+
+        Ok(Self {
+            k,
+            n: x.len(),
+            mmd2,
+            lambda,
+        })
+    }
+
+    /// Unbiased estimator `MMD²_u`. It can be negative when the samples are close.
+    pub fn mmd2(&self) -> f64 {
+        self.mmd2
+    }
+
+    /// p-value of `MMD²_u` against `permutations` random reassignments of the pooled sample to `x` and `y`.
+    pub fn permutation_p_value<R>(&self, permutations: usize, rng: &mut R) -> f64
+    where
+        R: Rng + ?Sized,
+    {
+        let mut index = (0..self.k.rows()).collect::<Vec<usize>>();
+        let exceeded = (0..permutations)
+            .filter(|_| {
+                index.shuffle(rng);
+                unbiased(&self.k, &index, self.n) >= self.mmd2
+            })
+            .count();
+
+        (exceeded + 1) as f64 / (permutations + 1) as f64
+    }
+
+    /// p-value of `MMD²_u` against `samples` draws from its asymptotic null distribution,
+    /// `N MMD²_u ~ (N² / nm) Σ_l λ_l (z_l² - 1)` with `z_l ~ N(0, 1)`,
+    /// where `λ_l` are the eigenvalues of the centred pooled Gram matrix divided by `N = n + m`.
+    /// The eigenvalues are computed in `new`, so this only draws the Gaussian samples.
+    pub fn spectral_p_value<R>(&self, samples: usize, rng: &mut R) -> f64
+    where
+        R: Rng + ?Sized,
+    {
+        let size = self.k.rows();
+        let (n, m) = (self.n as f64, (size - self.n) as f64);
+
+        let scale = (n + m).powi(2) / (n * m);
+        let statistic = (n + m) * self.mmd2;
+
+        let exceeded = (0..samples)
+            .filter(|_| {
+                let null = scale
+                    * self
+                        .lambda
+                        .iter()
+                        .map(|l| {
+                            let z: f64 = rng.sample(StandardNormal);
+                            l * (z.powi(2) - 1.0)
+                        })
+                        .sum::<f64>();
+                null >= statistic
+            })
+            .count();
+
+        (exceeded + 1) as f64 / (samples + 1) as f64
+    }
+}
+
+/// Linear-time estimator of `MMD²`, the mean of
+/// `h_i = k(x_2i, x_2i+1) + k(y_2i, y_2i+1) - k(x_2i, y_2i+1) - k(x_2i+1, y_2i)`
+/// over disjoint pairs. Unbiased, and usable on samples too large for a Gram matrix.
+/// Uses the first `2 ⌊min(n, m) / 2⌋` points of each sample.
+pub fn linear_time_mmd2<K, T>(
+    kernel: &K,
+    params: &[f64],
+    x: &[T],
+    y: &[T],
+) -> Result<f64, KernelError>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    let pairs = x.len().min(y.len()) / 2;
+    if pairs == 0 {
+        return Err(KernelError::InvalidArgument);
+    }
+
+    let sum = (0..pairs)
+        .into_par_iter()
+        .map(|i| {
+            let (x1, x2, y1, y2) = (&x[2 * i], &x[2 * i + 1], &y[2 * i], &y[2 * i + 1]);
+            Ok(
+                kernel.value(params, x1, x2)? + kernel.value(params, y1, y2)?
+                    - kernel.value(params, x1, y2)?
+                    - kernel.value(params, x2, y1)?,
+            )
+        })
+        .sum::<Result<f64, KernelError>>()?;
+
+    Ok(sum / pairs as f64)
+}
+
+/// `RBF` lengthscale `2 d²` from the median `d` of the pairwise distances of `x`,
+/// which corresponds to `exp(-|x - x'|² / 2d²)`. Pass the pooled sample for the two-sample test.
+pub fn median_heuristic(x: &[Vec<f64>]) -> Result<f64, KernelError> {
+    let mut distances = (0..x.len())
+        .into_par_iter()
+        .flat_map(|i| {
+            (i + 1..x.len())
+                .map(|j| {
+                    if x[i].len() != x[j].len() {
+                        return Err(KernelError::InvalidArgument);
+                    }
+                    Ok(x[i]
+                        .iter()
+                        .zip(x[j].iter())
+                        .map(|(a, b)| (a - b).powi(2))
+                        .sum::<f64>())
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Result<Vec<f64>, KernelError>>()?;
+    if distances.is_empty() {
+        return Err(KernelError::InvalidArgument);
+    }
+
+    distances.sort_by(|a, b| a.total_cmp(b));
+    let middle = distances.len() / 2;
+    let median = if distances.len() % 2 == 1 {
+        distances[middle]
+    } else {
+        (distances[middle - 1] + distances[middle]) / 2.0
+    };
+    if median <= 0.0 {
+        return Err(KernelError::InvalidArgument);
+    }
+
+    Ok(2.0 * median)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::prelude::*;
+    use rand_distr::StandardNormal;
+
+    fn sample(rng: &mut StdRng, n: usize, shift: f64) -> Vec<Vec<f64>> {
+        (0..n)
+            .map(|_| {
+                vec![
+                    rng.sample::<f64, _>(StandardNormal) + shift,
+                    rng.sample(StandardNormal),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_works() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let x = sample(&mut rng, 5, 0.0);
+        let y = sample(&mut rng, 4, 0.5);
+        let params = [1.0, 2.0];
+
+        let mmd = MaximumMeanDiscrepancy::new(&RBF, &params, &x, &y).unwrap();
+
+        let mean = |a: &[Vec<f64>], b: &[Vec<f64>], same: bool| {
+            let mut sum = 0.0;
+            let mut count = 0;
+            for (i, ai) in a.iter().enumerate() {
+                for (j, bj) in b.iter().enumerate() {
+                    if !same || i != j {
+                        sum += RBF.value(&params, ai, bj).unwrap();
+                        count += 1;
+                    }
+                }
+            }
+            sum / count as f64
+        };
+        let expected = mean(&x, &x, true) + mean(&y, &y, true) - 2.0 * mean(&x, &y, false);
+        assert!((mmd.mmd2() - expected).abs() < 1e-12);
+
+        let linear = linear_time_mmd2(&RBF, &params, &x, &y).unwrap();
+        let h = |i: usize| {
+            RBF.value(&params, &x[2 * i], &x[2 * i + 1]).unwrap()
+                + RBF.value(&params, &y[2 * i], &y[2 * i + 1]).unwrap()
+                - RBF.value(&params, &x[2 * i], &y[2 * i + 1]).unwrap()
+                - RBF.value(&params, &x[2 * i + 1], &y[2 * i]).unwrap()
+        };
+        assert!((linear - (h(0) + h(1)) / 2.0).abs() < 1e-12);
+
+        let median = median_heuristic(&[vec![0.0], vec![1.0], vec![3.0]]).unwrap();
+        assert!((median - 8.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn it_works2() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let x = sample(&mut rng, 60, 0.0);
+        let same = sample(&mut rng, 50, 0.0);
+        let shifted = sample(&mut rng, 50, 1.0);
+        let pooled = [&x[..], &shifted[..]].concat();
+        let params = [1.0, median_heuristic(&pooled).unwrap()];
+
+        let null = MaximumMeanDiscrepancy::new(&RBF, &params, &x, &same).unwrap();
+        let alternative = MaximumMeanDiscrepancy::new(&RBF, &params, &x, &shifted).unwrap();
+
+        assert!(null.permutation_p_value(200, &mut rng) > 0.05);
+        assert!(null.spectral_p_value(1000, &mut rng) > 0.05);
+        assert!(alternative.permutation_p_value(200, &mut rng) < 0.01);
+        assert!(alternative.spectral_p_value(1000, &mut rng) < 0.01);
+        assert!(linear_time_mmd2(&RBF, &params, &x, &shifted).unwrap() > 0.0);
+    }
+}
@@ -1,15 +1,15 @@
-use opensrdk_linear_algebra::Vector;
-
 use crate::KernelError;
 use crate::ParamsDifferentiableKernel;
 use crate::Value;
 use crate::ValueDifferentiableKernel;
 use crate::{KernelAdd, PositiveDefiniteKernel};
+use std::fmt::{self, Debug, Display, Formatter};
 use std::marker::PhantomData;
 use std::ops::Add;
-use std::{fmt::Debug, ops::Mul};
+use std::ops::Mul;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KernelMul<L, R, T>
 where
     L: PositiveDefiniteKernel<T>,
@@ -34,6 +34,26 @@ where
             phantom: PhantomData,
         }
     }
+
+    fn split_params<'a>(&self, params: &'a [f64]) -> Result<(&'a [f64], &'a [f64]), KernelError> {
+        if params.len() != self.params_len() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+
+        Ok(params.split_at(self.lhs.params_len()))
+    }
+}
+
+/// Writes `lhs * rhs`, parenthesizing operands that are sums.
+impl<L, R, T> Display for KernelMul<L, R, T>
+where
+    L: PositiveDefiniteKernel<T> + Display,
+    R: PositiveDefiniteKernel<T> + Display,
+    T: Value,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#} * {:#}", self.lhs, self.rhs)
+    }
 }
 
 impl<L, R, T> PositiveDefiniteKernel<T> for KernelMul<L, R, T>
@@ -46,10 +66,24 @@ where
         self.lhs.params_len() + self.rhs.params_len()
     }
 
+    fn params_names(&self) -> Vec<String> {
+        self.lhs
+            .params_names()
+            .into_iter()
+            .map(|name| format!("mul.lhs.{}", name))
+            .chain(
+                self.rhs
+                    .params_names()
+                    .into_iter()
+                    .map(|name| format!("mul.rhs.{}", name)),
+            )
+            .collect()
+    }
+
     fn value(&self, params: &[f64], x: &T, xprime: &T) -> Result<f64, KernelError> {
-        let lhs_params_len = self.lhs.params_len();
-        let fx = self.lhs.value(&params[..lhs_params_len], x, xprime)?;
-        let gx = self.rhs.value(&params[lhs_params_len..], x, xprime)?;
+        let (lhs_params, rhs_params) = self.split_params(params)?;
+        let fx = self.lhs.value(lhs_params, x, xprime)?;
+        let gx = self.rhs.value(rhs_params, x, xprime)?;
 
         let hx = fx * gx;
 
@@ -92,21 +126,21 @@ where
     T: Value,
 {
     fn ln_diff_value(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError> {
-        let diff_rhs = &self
-            .rhs
-            .ln_diff_value(params, x, xprime)
-            .unwrap()
-            .clone()
-            .col_mat();
-        let diff_lhs = &self
+        let (lhs_params, rhs_params) = self.split_params(params)?;
+        let diff = self
             .lhs
-            .ln_diff_value(params, x, xprime)
-            .unwrap()
-            .clone()
-            .col_mat();
-        let diff = (diff_rhs + diff_lhs.clone()).vec();
+            .ln_diff_value(lhs_params, x, xprime)?
+            .iter()
+            .zip(self.rhs.ln_diff_value(rhs_params, x, xprime)?.iter())
+            .map(|(diff_fx, diff_gx)| diff_fx + diff_gx)
+            .collect();
+
         Ok(diff)
     }
+
+    fn is_value_differentiable(&self) -> bool {
+        self.lhs.is_value_differentiable() && self.rhs.is_value_differentiable()
+    }
 }
 
 impl<L, R, T> ParamsDifferentiableKernel<T> for KernelMul<L, R, T>
@@ -116,19 +150,51 @@ where
     T: Value,
 {
     fn ln_diff_params(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError> {
-        let diff_rhs = &self
-            .rhs
-            .ln_diff_params(params, x, xprime)
-            .unwrap()
-            .clone()
-            .col_mat();
-        let diff_lhs = &self
-            .lhs
-            .ln_diff_params(params, x, xprime)
-            .unwrap()
-            .clone()
-            .col_mat();
-        let diff = (diff_rhs + diff_lhs.clone()).vec();
+        let (lhs_params, rhs_params) = self.split_params(params)?;
+        let diff = [
+            self.lhs.ln_diff_params(lhs_params, x, xprime)?,
+            self.rhs.ln_diff_params(rhs_params, x, xprime)?,
+        ]
+        .concat();
+
         Ok(diff)
     }
+
+    fn is_params_differentiable(&self) -> bool {
+        self.lhs.is_params_differentiable() && self.rhs.is_params_differentiable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let kernel = Constant * RBF * ARD(2);
+        let params = [0.8, 1.5, 2.0, 0.3, 0.6];
+        let x = vec![0.1, 0.5];
+        let xprime = vec![0.4, -0.2];
+
+        let params_check = check_params_gradient(&kernel, &params, &x, &xprime).unwrap();
+        let value_check = check_value_gradient(&kernel, &params, &x, &xprime).unwrap();
+
+        assert_eq!(params_check.analytic.len(), kernel.params_len());
+        assert!(params_check.max_error() < 1e-6, "{:?}", params_check);
+        assert!(value_check.max_error() < 1e-6, "{:?}", value_check);
+    }
+
+    #[test]
+    fn it_works2() {
+        let kernel = Periodic * (Exponential + Linear);
+        let params = [0.7, 1.3, 0.9];
+        let x = vec![0.6, 0.5];
+        let xprime = vec![0.4, 0.8];
+
+        let params_check = check_params_gradient(&kernel, &params, &x, &xprime).unwrap();
+        let value_check = check_value_gradient(&kernel, &params, &x, &xprime).unwrap();
+
+        assert!(params_check.max_error() < 1e-6, "{:?}", params_check);
+        assert!(value_check.max_error() < 1e-6, "{:?}", value_check);
+    }
 }
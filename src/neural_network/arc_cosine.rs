@@ -0,0 +1,187 @@
+use super::step::Step;
+use super::{Activation, ActivationFunction};
+use std::f64::consts::PI;
+
+/// Arc-cosine kernel of order `n`, the activation `Θ(u) u^n`.
+///
+/// `f = (k_xx k_x'x')^(n/2) J_n(θ) / (2π)` with
+/// `J_n(θ) = (-1)^n sin^(2n+1)(θ) (1/sinθ ∂/∂θ)^n ((π - θ) / sinθ)`, which is expanded into
+/// `(π - θ) p_n(cosθ) + sinθ q_n(cosθ)` for polynomials `p_n`, `q_n`.
+/// Order 0 is `Step` and order 1 is `ReLU`.
+/// https://papers.nips.cc/paper/2009/hash/5751ec3e9a4feab575962e78e006250d-Abstract.html
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArcCosine {
+    n: usize,
+    /// Coefficients of `p_m` and `q_m` for `m = 0..=n`, in ascending powers.
+    p: Vec<Vec<f64>>,
+    q: Vec<Vec<f64>>,
+}
+
+fn polynomial(coefficients: &[f64], c: f64) -> f64 {
+    coefficients.iter().rev().fold(0.0, |sum, a| sum * c + a)
+}
+
+/// `(1 - c^2) a' + b c a`
+fn raise(a: &[f64], b: f64) -> Vec<f64> {
+    let mut raised = vec![0.0; a.len() + 1];
+    for (i, &a_i) in a.iter().enumerate() {
+        if i > 0 {
+            raised[i - 1] += i as f64 * a_i;
+            raised[i + 1] -= i as f64 * a_i;
+        }
+        raised[i + 1] += b * a_i;
+    }
+
+    raised
+}
+
+impl ArcCosine {
+    pub fn new(n: usize) -> Self {
+        let mut p = vec![vec![1.0]];
+        let mut q = vec![vec![0.0]];
+
+        for m in 0..n {
+            // p_{m+1} = (1 - c^2) p_m' + (2m + 1) c p_m
+            // q_{m+1} = (1 - c^2) q_m' + 2m c q_m + p_m
+            let mut next_q = raise(&q[m], 2.0 * m as f64);
+            next_q.resize(next_q.len().max(p[m].len()), 0.0);
+            for (a, b) in next_q.iter_mut().zip(p[m].iter()) {
+                *a += b;
+            }
+            p.push(raise(&p[m], 2.0 * m as f64 + 1.0));
+            q.push(next_q);
+        }
+
+        Self { n, p, q }
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// `(k_xx k_x'x')^(m/2) J_m(θ) / (2π)`
+    fn f_m(&self, m: usize, sqrt: f64, cos: f64) -> f64 {
+        let theta = cos.acos();
+        let j =
+            (PI - theta) * polynomial(&self.p[m], cos) + theta.sin() * polynomial(&self.q[m], cos);
+
+        sqrt.powi(m as i32) * j / (2.0 * PI)
+    }
+
+    fn sqrt_cos(previous_layer_kernel: (f64, f64, f64)) -> (f64, f64) {
+        let sqrt = (previous_layer_kernel.1 * previous_layer_kernel.2).sqrt();
+        let cos = if sqrt == 0.0 {
+            0.0
+        } else {
+            (previous_layer_kernel.0 / sqrt).clamp(-1.0, 1.0)
+        };
+
+        (sqrt, cos)
+    }
+}
+
+impl ActivationFunction for ArcCosine {
+    fn f(&self, previous_layer_kernel: (f64, f64, f64)) -> f64 {
+        let (sqrt, cos) = Self::sqrt_cos(previous_layer_kernel);
+
+        self.f_m(self.n, sqrt, cos)
+    }
+
+    fn df(&self, previous_layer_kernel: (f64, f64, f64)) -> (f64, f64, f64) {
+        let (k0, k1, k2) = previous_layer_kernel;
+        let (sqrt, cos) = Self::sqrt_cos(previous_layer_kernel);
+        if sqrt == 0.0 {
+            return (0.0, 0.0, 0.0);
+        }
+
+        let n = self.n as f64;
+        // Price's theorem gives ∂f_n/∂k_xx' = n^2 f_{n-1}, and f_n is homogeneous of degree n/2 in k_xx and k_x'x'.
+        // For order 0, see `Step::df` for the limit at `θ = 0` or `π`.
+        let diff0 = if self.n == 0 {
+            let sin = (1.0 - cos.powi(2)).sqrt();
+            if sin == 0.0 {
+                0.0
+            } else {
+                1.0 / (2.0 * PI * sqrt * sin)
+            }
+        } else {
+            n.powi(2) * self.f_m(self.n - 1, sqrt, cos)
+        };
+        let f = self.f_m(self.n, sqrt, cos);
+
+        (
+            diff0,
+            (n * f - k0 * diff0) / (2.0 * k1),
+            (n * f - k0 * diff0) / (2.0 * k2),
+        )
+    }
+
+    fn diagonal_df(&self, k: f64) -> f64 {
+        if self.n == 0 {
+            return 0.0;
+        }
+
+        // f((k, k, k)) = k^n J_n(0) / (2π) with J_n(0) = π p_n(1)
+        self.n as f64 * k.powi(self.n as i32 - 1) * polynomial(&self.p[self.n], 1.0) / 2.0
+    }
+
+    fn derivative_kernel(&self, previous_layer_kernel: (f64, f64, f64)) -> f64 {
+        if self.n == 0 {
+            return Step.derivative_kernel(previous_layer_kernel);
+        }
+
+        self.df(previous_layer_kernel).0
+    }
+
+    fn activation(&self) -> Option<Activation> {
+        Some(Activation::ArcCosine(self.n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let kernels = [(0.3, 1.2, 0.8), (-0.5, 0.7, 1.1), (1.0, 1.0, 1.0)];
+
+        for &k in kernels.iter() {
+            assert!((ArcCosine::new(0).f(k) - Step.f(k)).abs() < 1e-12);
+            assert!((ArcCosine::new(1).f(k) - ReLU.f(k)).abs() < 1e-12);
+        }
+
+        // J_2 = 3 sinθ cosθ + (π - θ)(1 + 2 cos^2θ)
+        let theta = 1.1f64;
+        let expected = (3.0 * theta.sin() * theta.cos()
+            + (std::f64::consts::PI - theta) * (1.0 + 2.0 * theta.cos().powi(2)))
+            / (2.0 * std::f64::consts::PI);
+        assert!((ArcCosine::new(2).f((theta.cos(), 1.0, 1.0)) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn it_works2() {
+        let k = (0.3, 1.2, 0.8);
+        let h = 1e-6;
+
+        for n in 0..5 {
+            let kernel = ArcCosine::new(n);
+            let df = kernel.df(k);
+
+            let numerical = (
+                (kernel.f((k.0 + h, k.1, k.2)) - kernel.f((k.0 - h, k.1, k.2))) / (2.0 * h),
+                (kernel.f((k.0, k.1 + h, k.2)) - kernel.f((k.0, k.1 - h, k.2))) / (2.0 * h),
+                (kernel.f((k.0, k.1, k.2 + h)) - kernel.f((k.0, k.1, k.2 - h))) / (2.0 * h),
+            );
+            let numerical_diagonal = (kernel.f((0.7 + h, 0.7 + h, 0.7 + h))
+                - kernel.f((0.7 - h, 0.7 - h, 0.7 - h)))
+                / (2.0 * h);
+
+            assert!((df.0 - numerical.0).abs() < 1e-7, "{}", n);
+            assert!((df.1 - numerical.1).abs() < 1e-7, "{}", n);
+            assert!((df.2 - numerical.2).abs() < 1e-7, "{}", n);
+            assert!((kernel.diagonal_df(0.7) - numerical_diagonal).abs() < 1e-7);
+        }
+    }
+}
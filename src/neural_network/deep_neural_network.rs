@@ -1,94 +1,208 @@
 use super::ActivationFunction;
 use crate::{
-    Constant, KernelAdd, KernelError, KernelMul, Linear, ParamsDifferentiableKernel,
-    PositiveDefiniteKernel, ValueDifferentiableKernel,
+    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, PositiveDefiniteKernel,
+    ValueDifferentiableKernel,
 };
 use std::{
     fmt::Debug,
     ops::{Add, Mul},
+    sync::Arc,
 };
 
+/// `(k_xx', k_xx, k_x'x')` of a layer.
+type LayerKernel = (f64, f64, f64);
+
+/// Variables whose derivatives are propagated through the layers.
+#[derive(Clone, Copy, PartialEq)]
+enum Tangents {
+    None,
+    Params,
+    Value,
+}
+
 /// https://arxiv.org/abs/1711.00165
 #[derive(Clone, Debug)]
-pub struct DeepNeuralNetwork<'a> {
-    layers: Vec<&'a dyn ActivationFunction>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeepNeuralNetwork {
+    #[cfg_attr(feature = "serde", serde(with = "crate::neural_network::serde_layers"))]
+    layers: Vec<Arc<dyn ActivationFunction>>,
 }
 
-impl<'a> DeepNeuralNetwork<'a> {
-    pub fn new(layers: Vec<&'a dyn ActivationFunction>) -> Self {
+impl DeepNeuralNetwork {
+    pub fn new(layers: Vec<Arc<dyn ActivationFunction>>) -> Self {
         Self { layers }
     }
-}
-impl<'a> PositiveDefiniteKernel<Vec<f64>> for DeepNeuralNetwork<'a> {
-    fn params_len(&self) -> usize {
-        2 * (1 + self.layers.len())
+
+    pub fn builder() -> DeepNeuralNetworkBuilder {
+        DeepNeuralNetworkBuilder { layers: vec![] }
     }
 
-    fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
+    pub fn layers(&self) -> &[Arc<dyn ActivationFunction>] {
+        &self.layers
+    }
+
+    /// Runs the layer recursion, carrying the derivatives of each layer kernel along in forward mode.
+    /// Returns the kernel and the derivatives of its logarithm.
+    fn forward(
+        &self,
+        params: &[f64],
+        x: &[f64],
+        xprime: &[f64],
+        tangents: Tangents,
+    ) -> Result<(f64, Vec<f64>), KernelError> {
         if params.len() != self.params_len() {
-            return Err(KernelError::ParametersLengthMismatch.into());
+            return Err(KernelError::ParametersLengthMismatch);
         }
         if x.len() != xprime.len() {
-            return Err(KernelError::InvalidArgument.into());
+            return Err(KernelError::InvalidArgument);
         }
 
-        let layer0 = Constant + Constant * Linear;
-        let mut previous_layer_kernel = (
-            layer0.value(&params[0..2], x, xprime)?,
-            layer0.value(&params[0..2], x, x)?,
-            layer0.value(&params[0..2], xprime, xprime)?,
+        let dot = |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).map(|(a, b)| a * b).sum::<f64>();
+        let products = (dot(x, xprime), dot(x, x), dot(xprime, xprime));
+        let (sigma_b, sigma_w) = (params[0], params[1]);
+
+        let mut kernel = (
+            sigma_b + sigma_w * products.0,
+            sigma_b + sigma_w * products.1,
+            sigma_b + sigma_w * products.2,
         );
-        let params = &params[2..];
+        let mut diff: Vec<LayerKernel> = match tangents {
+            Tangents::None => vec![],
+            Tangents::Params => {
+                let mut diff = vec![(0.0, 0.0, 0.0); params.len()];
+                diff[0] = (1.0, 1.0, 1.0);
+                diff[1] = products;
+                diff
+            }
+            Tangents::Value => x
+                .iter()
+                .zip(xprime.iter())
+                .map(|(x_i, xprime_i)| (sigma_w * xprime_i, 2.0 * sigma_w * x_i, 0.0))
+                .collect(),
+        };
 
-        for (i, &layer) in self.layers.iter().enumerate() {
+        for (i, layer) in self.layers.iter().enumerate() {
             let sigma_b = params[(i + 1) * 2];
             let sigma_w = params[(i + 1) * 2 + 1];
-            let f = layer.f(previous_layer_kernel);
-            let fxx = layer.f((
-                previous_layer_kernel.1,
-                previous_layer_kernel.1,
-                previous_layer_kernel.1,
-            ));
-            let fxpxp = layer.f((
-                previous_layer_kernel.2,
-                previous_layer_kernel.2,
-                previous_layer_kernel.2,
-            ));
-
-            previous_layer_kernel = (
-                sigma_b + sigma_w * f,
-                sigma_b + sigma_w * fxx,
-                sigma_b + sigma_w * fxpxp,
+            let diagonal = |k: f64| (k, k, k);
+            let f = (
+                layer.f(kernel),
+                layer.f(diagonal(kernel.1)),
+                layer.f(diagonal(kernel.2)),
+            );
+
+            if tangents != Tangents::None {
+                let df = layer.df(kernel);
+                let dfxx = layer.diagonal_df(kernel.1);
+                let dfxpxp = layer.diagonal_df(kernel.2);
+
+                for d in diff.iter_mut() {
+                    *d = (
+                        sigma_w * (df.0 * d.0 + df.1 * d.1 + df.2 * d.2),
+                        sigma_w * dfxx * d.1,
+                        sigma_w * dfxpxp * d.2,
+                    );
+                }
+                if tangents == Tangents::Params {
+                    diff[(i + 1) * 2] = (1.0, 1.0, 1.0);
+                    diff[(i + 1) * 2 + 1] = f;
+                }
+            }
+
+            kernel = (
+                sigma_b + sigma_w * f.0,
+                sigma_b + sigma_w * f.1,
+                sigma_b + sigma_w * f.2,
             );
         }
 
-        Ok(previous_layer_kernel.0)
+        let diff = diff.iter().map(|d| d.0 / kernel.0).collect();
+
+        Ok((kernel.0, diff))
+    }
+}
+
+/// Stacks the hidden layers of a `DeepNeuralNetwork` from the input side.
+#[derive(Clone, Debug)]
+pub struct DeepNeuralNetworkBuilder {
+    layers: Vec<Arc<dyn ActivationFunction>>,
+}
+
+impl DeepNeuralNetworkBuilder {
+    pub fn layer<A>(mut self, activation: A) -> Self
+    where
+        A: ActivationFunction + 'static,
+    {
+        self.layers.push(Arc::new(activation));
+        self
+    }
+
+    /// Appends `depth` layers sharing the same activation.
+    pub fn layers<A>(mut self, depth: usize, activation: A) -> Self
+    where
+        A: ActivationFunction + 'static,
+    {
+        let activation: Arc<dyn ActivationFunction> = Arc::new(activation);
+        self.layers
+            .extend((0..depth).map(|_| Arc::clone(&activation)));
+        self
+    }
+
+    pub fn build(self) -> DeepNeuralNetwork {
+        DeepNeuralNetwork::new(self.layers)
     }
 }
 
-impl<'a> ValueDifferentiableKernel<Vec<f64>> for DeepNeuralNetwork<'a> {
+impl PositiveDefiniteKernel<Vec<f64>> for DeepNeuralNetwork {
+    fn params_len(&self) -> usize {
+        2 * (1 + self.layers.len())
+    }
+
+    fn params_names(&self) -> Vec<String> {
+        (0..1 + self.layers.len())
+            .flat_map(|i| {
+                vec![
+                    format!("deep_neural_network.layer{}.sigma_b", i),
+                    format!("deep_neural_network.layer{}.sigma_w", i),
+                ]
+            })
+            .collect()
+    }
+
+    fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
+        let (fx, _) = self.forward(params, x, xprime, Tangents::None)?;
+
+        Ok(fx)
+    }
+}
+
+impl ValueDifferentiableKernel<Vec<f64>> for DeepNeuralNetwork {
     fn ln_diff_value(
         &self,
         params: &[f64],
         x: &Vec<f64>,
         xprime: &Vec<f64>,
     ) -> Result<Vec<f64>, KernelError> {
-        todo!()
+        let (_, diff) = self.forward(params, x, xprime, Tangents::Value)?;
+
+        Ok(diff)
     }
 }
 
-impl<'a> ParamsDifferentiableKernel<Vec<f64>> for DeepNeuralNetwork<'a> {
+impl ParamsDifferentiableKernel<Vec<f64>> for DeepNeuralNetwork {
     fn ln_diff_params(
         &self,
         params: &[f64],
         x: &Vec<f64>,
         xprime: &Vec<f64>,
     ) -> Result<Vec<f64>, KernelError> {
-        todo!()
+        let (_, diff) = self.forward(params, x, xprime, Tangents::Params)?;
+
+        Ok(diff)
     }
 }
 
-impl<'a, R> Add<R> for DeepNeuralNetwork<'a>
+impl<R> Add<R> for DeepNeuralNetwork
 where
     R: PositiveDefiniteKernel<Vec<f64>>,
 {
@@ -98,7 +212,7 @@ where
     }
 }
 
-impl<'a, R> Mul<R> for DeepNeuralNetwork<'a>
+impl<R> Mul<R> for DeepNeuralNetwork
 where
     R: PositiveDefiniteKernel<Vec<f64>>,
 {
@@ -112,11 +226,11 @@ where
 #[cfg(test)]
 mod tests {
     use crate::*;
+    use std::sync::Arc;
 
     #[test]
     fn it_works() {
-        let activfunc = ReLU;
-        let kernel = DeepNeuralNetwork::new(vec![&activfunc]);
+        let kernel = DeepNeuralNetwork::new(vec![Arc::new(ReLU)]);
 
         let test_value = kernel.value(
             &[1.0, 1.0, 3.0, 4.0, 6.0],
@@ -129,4 +243,83 @@ mod tests {
             _ => panic!(),
         };
     }
+
+    #[test]
+    fn it_works2() {
+        let kernel = DeepNeuralNetwork::builder().layer(ReLU).build();
+
+        let test_value = kernel
+            .value(
+                &[1.0, 1.0, 3.0, 4.0],
+                &vec![1.0, 0.0, 0.0],
+                &vec![1.0, 0.0, 0.0],
+            )
+            .unwrap();
+
+        assert!((test_value - 7.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn check_gradients() {
+        let kernel = DeepNeuralNetwork::builder()
+            .layer(ReLU)
+            .layers(2, ReLU)
+            .build();
+
+        assert_gradients(&kernel, &[1.5, 0.8, 0.6, 1.2, 0.9, 0.7, 1.1, 0.5], 3);
+    }
+
+    #[test]
+    fn it_works3() {
+        fn build(depth: usize) -> DeepNeuralNetwork {
+            DeepNeuralNetwork::builder().layers(depth, ReLU).build()
+        }
+        let kernel = build(2);
+        let params = [1.0, 1.0, 0.5, 2.0, 0.5, 2.0];
+        let x = vec![0.3, -0.4];
+        let xprime = vec![0.1, 0.8];
+
+        let expected = kernel.value(&params, &x, &xprime).unwrap();
+        let test_value = std::thread::spawn(move || kernel.value(&params, &x, &xprime).unwrap())
+            .join()
+            .unwrap();
+
+        assert_eq!(build(2).layers().len(), 2);
+        assert_eq!(test_value, expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let kernel = DeepNeuralNetwork::builder()
+            .layer(ReLU)
+            .layer(Erf)
+            .layer(Step)
+            .layer(ArcCosine::new(2))
+            .build();
+        let params = vec![0.5, 1.2, 0.1, 0.9, 0.3, 1.1, 0.2, 0.8, 0.4, 1.3];
+        let x = vec![0.3, -0.2];
+        let xprime = vec![-0.4, 0.5];
+
+        let dnn = KernelWithParams::new(kernel.clone(), params.clone());
+        let json = serde_json::to_string(&dnn).unwrap();
+        let restored: KernelWithParams<DeepNeuralNetwork> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            restored.value(&x, &xprime).unwrap().to_bits(),
+            dnn.value(&x, &xprime).unwrap().to_bits()
+        );
+
+        let ntk = KernelWithParams::new(NeuralTangent::from(kernel), params);
+        let json = serde_json::to_string(&ntk).unwrap();
+        let restored: KernelWithParams<NeuralTangent> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            restored.value(&x, &xprime).unwrap().to_bits(),
+            ntk.value(&x, &xprime).unwrap().to_bits()
+        );
+
+        let kernel = DeepNeuralNetwork::builder()
+            .layer(GaussHermite::gelu(20))
+            .build();
+        assert!(serde_json::to_string(&kernel).is_err());
+    }
 }
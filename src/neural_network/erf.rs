@@ -0,0 +1,62 @@
+use super::{Activation, ActivationFunction};
+use std::f64::consts::PI;
+
+/// Error function activation, a sigmoidal activation with a closed form kernel.
+///
+/// https://papers.nips.cc/paper/1996/hash/ae5e3ce40e0404a45ecacaaf05e5f735-Abstract.html
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Erf;
+
+impl Erf {
+    /// `2 k_xx' / sqrt((1 + 2 k_xx)(1 + 2 k_x'x'))`
+    fn z(previous_layer_kernel: (f64, f64, f64)) -> f64 {
+        let (k0, k1, k2) = previous_layer_kernel;
+
+        (2.0 * k0 / ((1.0 + 2.0 * k1) * (1.0 + 2.0 * k2)).sqrt()).clamp(-1.0, 1.0)
+    }
+}
+
+impl ActivationFunction for Erf {
+    fn f(&self, previous_layer_kernel: (f64, f64, f64)) -> f64 {
+        2.0 / PI * Self::z(previous_layer_kernel).asin()
+    }
+
+    fn df(&self, previous_layer_kernel: (f64, f64, f64)) -> (f64, f64, f64) {
+        let (_, k1, k2) = previous_layer_kernel;
+        let z = Self::z(previous_layer_kernel);
+        let diff_z = 2.0 / (PI * (1.0 - z.powi(2)).sqrt());
+
+        (
+            diff_z * 2.0 / ((1.0 + 2.0 * k1) * (1.0 + 2.0 * k2)).sqrt(),
+            -diff_z * z / (1.0 + 2.0 * k1),
+            -diff_z * z / (1.0 + 2.0 * k2),
+        )
+    }
+
+    fn activation(&self) -> Option<Activation> {
+        Some(Activation::Erf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let k = (0.3, 1.2, 0.8);
+        let df = Erf.df(k);
+        let h = 1e-6;
+
+        let numerical = (
+            (Erf.f((k.0 + h, k.1, k.2)) - Erf.f((k.0 - h, k.1, k.2))) / (2.0 * h),
+            (Erf.f((k.0, k.1 + h, k.2)) - Erf.f((k.0, k.1 - h, k.2))) / (2.0 * h),
+            (Erf.f((k.0, k.1, k.2 + h)) - Erf.f((k.0, k.1, k.2 - h))) / (2.0 * h),
+        );
+
+        assert!((df.0 - numerical.0).abs() < 1e-8);
+        assert!((df.1 - numerical.1).abs() < 1e-8);
+        assert!((df.2 - numerical.2).abs() < 1e-8);
+    }
+}
@@ -0,0 +1,158 @@
+use super::ActivationFunction;
+use crate::special::{erf, gauss_hermite};
+use std::f64::consts::PI;
+use std::fmt::{self, Debug, Formatter};
+
+/// Activation given as any scalar function, e.g. GELU or Swish, whose kernel is computed by
+/// two-dimensional Gauss–Hermite quadrature of `E[φ(u) φ(v)]`.
+///
+/// The partial derivatives use Price's theorem, `∂/∂k_xx' E[φ(u) φ(v)] = E[φ'(u) φ'(v)]` and
+/// `∂/∂k_xx E[φ(u) φ(v)] = E[φ''(u) φ(v)] / 2`, with `φ'` and `φ''` by central differences.
+#[derive(Clone)]
+pub struct GaussHermite<F>
+where
+    F: Fn(f64) -> f64 + Clone + Send + Sync,
+{
+    function: F,
+    nodes: Vec<f64>,
+    weights: Vec<f64>,
+}
+
+impl<F> GaussHermite<F>
+where
+    F: Fn(f64) -> f64 + Clone + Send + Sync,
+{
+    /// `order` is the number of quadrature nodes in each dimension.
+    pub fn new(function: F, order: usize) -> Self {
+        let (nodes, weights) = gauss_hermite(order);
+
+        Self {
+            function,
+            nodes: nodes.iter().map(|t| 2f64.sqrt() * t).collect(),
+            weights: weights.iter().map(|w| w / PI.sqrt()).collect(),
+        }
+    }
+
+    fn diff(&self, t: f64) -> f64 {
+        let h = 1e-5 * (1.0 + t.abs());
+
+        ((self.function)(t + h) - (self.function)(t - h)) / (2.0 * h)
+    }
+
+    fn diff2(&self, t: f64) -> f64 {
+        let h = 1e-4 * (1.0 + t.abs());
+
+        ((self.function)(t + h) - 2.0 * (self.function)(t) + (self.function)(t - h)) / h.powi(2)
+    }
+
+    /// `E[g(u) h(v)]`
+    fn expectation<G, H>(&self, previous_layer_kernel: (f64, f64, f64), g: G, h: H) -> f64
+    where
+        G: Fn(f64) -> f64,
+        H: Fn(f64) -> f64,
+    {
+        let (k0, k1, k2) = previous_layer_kernel;
+        let sqrt = (k1 * k2).sqrt();
+        let rho = if sqrt == 0.0 {
+            0.0
+        } else {
+            (k0 / sqrt).clamp(-1.0, 1.0)
+        };
+        let orthogonal = (1.0 - rho.powi(2)).sqrt();
+
+        self.nodes
+            .iter()
+            .zip(self.weights.iter())
+            .map(|(z1, w1)| {
+                let gu = g(k1.sqrt() * z1);
+                let hv = self
+                    .nodes
+                    .iter()
+                    .zip(self.weights.iter())
+                    .map(|(z2, w2)| w2 * h(k2.sqrt() * (rho * z1 + orthogonal * z2)))
+                    .sum::<f64>();
+
+                w1 * gu * hv
+            })
+            .sum()
+    }
+}
+
+impl GaussHermite<fn(f64) -> f64> {
+    /// `u Φ(u)` with the standard normal distribution function `Φ`.
+    pub fn gelu(order: usize) -> Self {
+        Self::new(|t| t * 0.5 * (1.0 + erf(t / 2f64.sqrt())), order)
+    }
+
+    /// `u / (1 + exp(-u))`
+    pub fn swish(order: usize) -> Self {
+        Self::new(|t| t / (1.0 + (-t).exp()), order)
+    }
+}
+
+impl<F> Debug for GaussHermite<F>
+where
+    F: Fn(f64) -> f64 + Clone + Send + Sync,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "GaussHermite {{ order: {} }}", self.nodes.len())
+    }
+}
+
+impl<F> ActivationFunction for GaussHermite<F>
+where
+    F: Fn(f64) -> f64 + Clone + Send + Sync,
+{
+    fn f(&self, previous_layer_kernel: (f64, f64, f64)) -> f64 {
+        self.expectation(previous_layer_kernel, &self.function, &self.function)
+    }
+
+    fn df(&self, previous_layer_kernel: (f64, f64, f64)) -> (f64, f64, f64) {
+        let diff = |t| self.diff(t);
+        let diff2 = |t| self.diff2(t);
+
+        (
+            self.expectation(previous_layer_kernel, diff, diff),
+            self.expectation(previous_layer_kernel, diff2, &self.function) / 2.0,
+            self.expectation(previous_layer_kernel, &self.function, diff2) / 2.0,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::special::erf;
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let activation = GaussHermite::new(erf, 40);
+
+        for &k in [(0.3, 1.2, 0.8), (-0.5, 0.7, 1.1), (0.6, 0.6, 0.6)].iter() {
+            let df = activation.df(k);
+            let expected = Erf.df(k);
+
+            assert!((activation.f(k) - Erf.f(k)).abs() < 1e-8);
+            assert!((df.0 - expected.0).abs() < 1e-6);
+            assert!((df.1 - expected.1).abs() < 1e-6);
+            assert!((df.2 - expected.2).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn it_works2() {
+        let kernel = DeepNeuralNetwork::builder()
+            .layer(GaussHermite::gelu(32))
+            .layer(GaussHermite::swish(32))
+            .build();
+        let params = [0.2, 1.0, 0.1, 1.5, 0.1, 1.5];
+        let x = vec![0.3, -0.4];
+        let xprime = vec![0.1, 0.8];
+
+        let params_check = check_params_gradient(&kernel, &params, &x, &xprime).unwrap();
+        let value_check = check_value_gradient(&kernel, &params, &x, &xprime).unwrap();
+
+        assert!(params_check.max_error() < 1e-5, "{:?}", params_check);
+        assert!(value_check.max_error() < 1e-5, "{:?}", value_check);
+    }
+}
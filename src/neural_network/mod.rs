@@ -1,8 +1,99 @@
 use std::fmt::Debug;
+use std::sync::Arc;
 
+pub mod arc_cosine;
 pub mod deep_neural_network;
+pub mod erf;
+pub mod gauss_hermite;
+pub mod neural_tangent;
 pub mod relu;
+pub mod step;
 
+/// Activation of a hidden layer of an infinitely wide network.
+///
+/// With `(u, v)` jointly Gaussian with covariance `[[k_xx, k_xx'], [k_xx', k_x'x']]`, `f` is `E[φ(u) φ(v)]`.
 pub trait ActivationFunction: Debug + Send + Sync {
+    /// Kernel of the next layer before scaling, given `(k_xx', k_xx, k_x'x')` of the previous layer.
     fn f(&self, previous_layer_kernel: (f64, f64, f64)) -> f64;
+
+    /// Partial derivatives of `f` with respect to `(k_xx', k_xx, k_x'x')`.
+    fn df(&self, previous_layer_kernel: (f64, f64, f64)) -> (f64, f64, f64);
+
+    /// Derivative of `f((k, k, k))` with respect to `k`.
+    fn diagonal_df(&self, k: f64) -> f64 {
+        let df = self.df((k, k, k));
+
+        df.0 + df.1 + df.2
+    }
+
+    /// `E[φ'(u) φ'(v)]`, used by the neural tangent kernel.
+    ///
+    /// By Price's theorem this equals the partial derivative of `f` with respect to `k_xx'`.
+    fn derivative_kernel(&self, previous_layer_kernel: (f64, f64, f64)) -> f64 {
+        self.df(previous_layer_kernel).0
+    }
+
+    /// This activation as an `Activation`, or `None` for one without a closed form such as `GaussHermite`.
+    fn activation(&self) -> Option<Activation> {
+        None
+    }
+}
+
+/// Activations with a closed form, which is how the layers of `DeepNeuralNetwork` and `NeuralTangent` are serialized.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Activation {
+    ReLU,
+    Erf,
+    Step,
+    ArcCosine(usize),
+}
+
+impl Activation {
+    pub fn into_arc(self) -> Arc<dyn ActivationFunction> {
+        match self {
+            Activation::ReLU => Arc::new(relu::ReLU),
+            Activation::Erf => Arc::new(erf::Erf),
+            Activation::Step => Arc::new(step::Step),
+            Activation::ArcCosine(n) => Arc::new(arc_cosine::ArcCosine::new(n)),
+        }
+    }
+}
+
+/// `serde(with)` for the layers, which fails to serialize activations that are not an `Activation`.
+#[cfg(feature = "serde")]
+pub(crate) mod serde_layers {
+    use super::{Activation, ActivationFunction};
+    use serde::{ser::Error, Deserialize, Deserializer, Serialize, Serializer};
+    use std::sync::Arc;
+
+    pub(crate) fn serialize<S>(
+        layers: &[Arc<dyn ActivationFunction>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        layers
+            .iter()
+            .map(|layer| {
+                layer
+                    .activation()
+                    .ok_or_else(|| S::Error::custom(format!("{:?} is not serializable", layer)))
+            })
+            .collect::<Result<Vec<Activation>, S::Error>>()?
+            .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<Vec<Arc<dyn ActivationFunction>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<Activation>::deserialize(deserializer)?
+            .into_iter()
+            .map(Activation::into_arc)
+            .collect())
+    }
 }
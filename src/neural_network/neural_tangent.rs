@@ -0,0 +1,157 @@
+use super::ActivationFunction;
+use crate::{DeepNeuralNetwork, KernelAdd, KernelError, KernelMul, PositiveDefiniteKernel};
+use std::{
+    fmt::Debug,
+    ops::{Add, Mul},
+    sync::Arc,
+};
+
+/// Neural tangent kernel of the fully connected network described by the same layers and params as
+/// `DeepNeuralNetwork`.
+///
+/// `Θ^l = Σ^l + σ_w^l Σ̇^l Θ^(l-1)` with `Θ^0 = Σ^0`, where `Σ^l` is the `DeepNeuralNetwork` kernel of
+/// layer `l` and `Σ̇^l` the `ActivationFunction::derivative_kernel` of `Σ^(l-1)`.
+/// https://arxiv.org/abs/1806.07572
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NeuralTangent {
+    #[cfg_attr(feature = "serde", serde(with = "crate::neural_network::serde_layers"))]
+    layers: Vec<Arc<dyn ActivationFunction>>,
+}
+
+impl NeuralTangent {
+    pub fn new(layers: Vec<Arc<dyn ActivationFunction>>) -> Self {
+        Self { layers }
+    }
+
+    pub fn layers(&self) -> &[Arc<dyn ActivationFunction>] {
+        &self.layers
+    }
+}
+
+impl From<DeepNeuralNetwork> for NeuralTangent {
+    fn from(kernel: DeepNeuralNetwork) -> Self {
+        Self::new(kernel.layers().to_vec())
+    }
+}
+
+impl PositiveDefiniteKernel<Vec<f64>> for NeuralTangent {
+    fn params_len(&self) -> usize {
+        2 * (1 + self.layers.len())
+    }
+
+    fn params_names(&self) -> Vec<String> {
+        (0..1 + self.layers.len())
+            .flat_map(|i| {
+                vec![
+                    format!("neural_tangent.layer{}.sigma_b", i),
+                    format!("neural_tangent.layer{}.sigma_w", i),
+                ]
+            })
+            .collect()
+    }
+
+    fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
+        if params.len() != self.params_len() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+        if x.len() != xprime.len() {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let dot = |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).map(|(a, b)| a * b).sum::<f64>();
+        let (sigma_b, sigma_w) = (params[0], params[1]);
+
+        let mut kernel = (
+            sigma_b + sigma_w * dot(x, xprime),
+            sigma_b + sigma_w * dot(x, x),
+            sigma_b + sigma_w * dot(xprime, xprime),
+        );
+        let mut tangent = kernel.0;
+
+        for (i, layer) in self.layers.iter().enumerate() {
+            let sigma_b = params[(i + 1) * 2];
+            let sigma_w = params[(i + 1) * 2 + 1];
+            let derivative = layer.derivative_kernel(kernel);
+
+            kernel = (
+                sigma_b + sigma_w * layer.f(kernel),
+                sigma_b + sigma_w * layer.f((kernel.1, kernel.1, kernel.1)),
+                sigma_b + sigma_w * layer.f((kernel.2, kernel.2, kernel.2)),
+            );
+            tangent = kernel.0 + sigma_w * derivative * tangent;
+        }
+
+        Ok(tangent)
+    }
+}
+
+impl<R> Add<R> for NeuralTangent
+where
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelAdd<Self, R, Vec<f64>>;
+
+    fn add(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<R> Mul<R> for NeuralTangent
+where
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelMul<Self, R, Vec<f64>>;
+
+    fn mul(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn it_works() {
+        let depth = 3;
+        let kernel = NeuralTangent::from(DeepNeuralNetwork::builder().layers(depth, ReLU).build());
+        // He initialization on unit vectors, https://arxiv.org/abs/1904.11955
+        let params = [0.0, 1.0, 0.0, 2.0, 0.0, 2.0, 0.0, 2.0];
+        let x = vec![0.6, 0.8];
+        let xprime = vec![1.0, 0.0];
+
+        let kappa0 = |rho: f64| (PI - rho.acos()) / PI;
+        let kappa1 = |rho: f64| ((1.0 - rho * rho).sqrt() + (PI - rho.acos()) * rho) / PI;
+        let mut sigma = vec![0.6];
+        let mut sigma_dot = vec![0.0];
+        for h in 0..depth {
+            sigma_dot.push(kappa0(sigma[h]));
+            sigma.push(kappa1(sigma[h]));
+        }
+        let expected = (0..=depth)
+            .map(|h| sigma[h] * sigma_dot[h + 1..].iter().product::<f64>())
+            .sum::<f64>();
+
+        let test_value = kernel.value(&params, &x, &xprime).unwrap();
+
+        assert!((test_value - expected).abs() < 1e-12);
+        assert!((kernel.value(&params, &x, &x).unwrap() - (depth + 1) as f64).abs() < 1e-12);
+    }
+
+    #[test]
+    fn it_works2() {
+        let kernel = NeuralTangent::new(vec![]);
+
+        let test_value = kernel
+            .value(&[0.5, 2.0], &vec![1.0, 2.0], &vec![3.0, -1.0])
+            .unwrap();
+
+        assert_eq!(test_value, 2.5);
+        match kernel.value(&[0.5], &vec![1.0], &vec![1.0]) {
+            Err(KernelError::ParametersLengthMismatch) => (),
+            _ => panic!(),
+        };
+    }
+}
@@ -1,14 +1,70 @@
-use super::ActivationFunction;
+use super::{Activation, ActivationFunction};
 use std::f64::consts::PI;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReLU;
 
+impl ReLU {
+    /// `sqrt(k_xx * k_x'x')` and the angle between `x` and `x'` in the feature space.
+    fn angle(previous_layer_kernel: (f64, f64, f64)) -> (f64, f64) {
+        let sqrt = (previous_layer_kernel.1 * previous_layer_kernel.2).sqrt();
+        let theta = (previous_layer_kernel.0 / sqrt).clamp(-1.0, 1.0).acos();
+
+        (sqrt, theta)
+    }
+}
+
 impl ActivationFunction for ReLU {
     fn f(&self, previous_layer_kernel: (f64, f64, f64)) -> f64 {
-        let sqrt = (previous_layer_kernel.1 * previous_layer_kernel.2).sqrt();
-        let theta = (previous_layer_kernel.0 / sqrt).acos();
+        let (sqrt, theta) = Self::angle(previous_layer_kernel);
+        if sqrt == 0.0 {
+            return 0.0;
+        }
+
+        sqrt * (theta.sin() + (PI - theta) * theta.cos()) / (2.0 * PI)
+    }
+
+    fn df(&self, previous_layer_kernel: (f64, f64, f64)) -> (f64, f64, f64) {
+        let (sqrt, theta) = Self::angle(previous_layer_kernel);
+        if sqrt == 0.0 {
+            return (0.0, 0.0, 0.0);
+        }
+
+        // sqrt(k_xx * k_x'x' - k_xx'^2)
+        let sin = sqrt * theta.sin();
+
+        (
+            (PI - theta) / (2.0 * PI),
+            sin / (4.0 * PI * previous_layer_kernel.1),
+            sin / (4.0 * PI * previous_layer_kernel.2),
+        )
+    }
+
+    fn activation(&self) -> Option<Activation> {
+        Some(Activation::ReLU)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let k = (0.3, 1.2, 0.8);
+        let df = ReLU.df(k);
+        let h = 1e-6;
+
+        let numerical = (
+            (ReLU.f((k.0 + h, k.1, k.2)) - ReLU.f((k.0 - h, k.1, k.2))) / (2.0 * h),
+            (ReLU.f((k.0, k.1 + h, k.2)) - ReLU.f((k.0, k.1 - h, k.2))) / (2.0 * h),
+            (ReLU.f((k.0, k.1, k.2 + h)) - ReLU.f((k.0, k.1, k.2 - h))) / (2.0 * h),
+        );
 
-        sqrt * (theta.sin() + (PI - theta) * theta.cos()) / 2.0 * PI
+        assert!((ReLU.f((2.0, 2.0, 2.0)) - 1.0).abs() < 1e-12);
+        assert!((df.0 - numerical.0).abs() < 1e-8);
+        assert!((df.1 - numerical.1).abs() < 1e-8);
+        assert!((df.2 - numerical.2).abs() < 1e-8);
     }
 }
@@ -0,0 +1,93 @@
+use super::{Activation, ActivationFunction};
+use std::f64::consts::PI;
+
+/// Heaviside step activation, the arc-cosine kernel of order 0.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Step;
+
+impl ActivationFunction for Step {
+    fn f(&self, previous_layer_kernel: (f64, f64, f64)) -> f64 {
+        let sqrt = (previous_layer_kernel.1 * previous_layer_kernel.2).sqrt();
+        let cos = if sqrt == 0.0 {
+            0.0
+        } else {
+            (previous_layer_kernel.0 / sqrt).clamp(-1.0, 1.0)
+        };
+
+        (PI - cos.acos()) / (2.0 * PI)
+    }
+
+    /// At `θ = 0` or `π`, as on the Gram diagonal, `f` is constant while `k_xx'^2 = k_xx k_x'x'` holds,
+    /// so this returns the limit `0` instead of the diverging partial derivatives.
+    fn df(&self, previous_layer_kernel: (f64, f64, f64)) -> (f64, f64, f64) {
+        let (k0, k1, k2) = previous_layer_kernel;
+        let sin2 = k1 * k2 - k0.powi(2);
+        if k1 * k2 == 0.0 || sin2 <= 0.0 {
+            return (0.0, 0.0, 0.0);
+        }
+
+        let diff0 = 1.0 / (2.0 * PI * sin2.sqrt());
+
+        (diff0, -k0 * diff0 / (2.0 * k1), -k0 * diff0 / (2.0 * k2))
+    }
+
+    fn diagonal_df(&self, _k: f64) -> f64 {
+        0.0
+    }
+
+    /// `E[δ(u) δ(v)]`, which diverges at `θ = 0` unlike `df`.
+    fn derivative_kernel(&self, previous_layer_kernel: (f64, f64, f64)) -> f64 {
+        let (k0, k1, k2) = previous_layer_kernel;
+        if k1 * k2 == 0.0 {
+            return 0.0;
+        }
+
+        1.0 / (2.0 * PI * (k1 * k2 - k0.powi(2)).max(0.0).sqrt())
+    }
+
+    fn activation(&self) -> Option<Activation> {
+        Some(Activation::Step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        assert!((Step.f((2.0, 2.0, 2.0)) - 0.5).abs() < 1e-12);
+        assert!((Step.f((0.0, 1.0, 3.0)) - 0.25).abs() < 1e-12);
+        assert!(Step.f((-1.0, 1.0, 1.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn it_works2() {
+        let x = vec![0.3, -0.4, 0.5];
+        let params = [0.5; 6];
+
+        for kernel in [
+            DeepNeuralNetwork::builder().layers(2, Step).build(),
+            DeepNeuralNetwork::builder()
+                .layers(2, ArcCosine::new(0))
+                .build(),
+        ] {
+            let diff = kernel.ln_diff_params(&params, &x, &x).unwrap();
+
+            assert!(diff.iter().all(|d| d.is_finite()), "{:?}", diff);
+            assert!(
+                check_params_gradient(&kernel, &params, &x, &x)
+                    .unwrap()
+                    .max_error()
+                    < 1e-6
+            );
+            // The value has a cusp at `x == x'`, where `ln_diff_value` takes the symmetric limit.
+            assert!(kernel
+                .ln_diff_value(&params, &x, &x)
+                .unwrap()
+                .iter()
+                .all(|d| d.is_finite()));
+        }
+    }
+}
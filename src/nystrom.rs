@@ -0,0 +1,423 @@
+use crate::linalg::{multiply, symmetric_eigen};
+use crate::{KernelError, PositiveDefiniteKernel, Value};
+use opensrdk_linear_algebra::Matrix;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+
+/// Eigenvalues of the landmark Gram matrix below this ratio to the largest one are dropped.
+const RELATIVE_TOLERANCE: f64 = 1e-10;
+
+/// How `Nystrom` picks its landmarks among the inputs.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LandmarkSelection {
+    /// Uniformly without replacement.
+    Uniform,
+    /// Kernel k-means++ seeding, each landmark drawn with probability proportional to
+    /// the squared feature-space distance to the nearest landmark so far.
+    /// Stops with fewer landmarks than requested once every remaining point coincides with a landmark.
+    KMeansPlusPlus,
+    /// Without replacement with probability proportional to the ridge leverage scores
+    /// `(K (K + λ I)^-1)_ii`, approximated from a uniform Nyström sketch of the same size.
+    /// Stops with fewer landmarks than requested once the scores of the remaining points are zero.
+    /// https://arxiv.org/abs/1411.0306
+    RidgeLeverageScore { lambda: f64 },
+}
+
+/// Low-rank approximation `K ≈ L L^T` of the Gram matrix of `x` through `m` landmarks `Z`,
+/// with `L = K(X, Z) U Λ^-1/2` for the eigendecomposition `K(Z, Z) = U Λ U^T`.
+///
+/// Built by `NystromBuilder`. `transform` maps new points to the same features, and as a `PositiveDefiniteKernel` without params
+/// it evaluates `k(x, Z) K(Z, Z)^+ k(Z, x')`.
+/// https://papers.nips.cc/paper/2000/hash/19de10adbaa1b2ee13f77f679fa1483a-Abstract.html
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Nystrom<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    kernel: K,
+    params: Vec<f64>,
+    landmarks: Vec<T>,
+    projection: Matrix,
+    factor: Matrix,
+}
+
+impl<K, T> Nystrom<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    pub fn kernel_ref(&self) -> &K {
+        &self.kernel
+    }
+
+    pub fn params(&self) -> &[f64] {
+        &self.params
+    }
+
+    /// Landmarks actually selected, which can be fewer than requested; see `LandmarkSelection`.
+    pub fn landmarks(&self) -> &[T] {
+        &self.landmarks
+    }
+
+    /// Number of features, at most the number of landmarks.
+    pub fn rank(&self) -> usize {
+        self.projection.cols()
+    }
+
+    /// `L` with `x.len()` rows and `rank()` columns, such that `K ≈ L L^T`.
+    pub fn factor(&self) -> &Matrix {
+        &self.factor
+    }
+
+    /// Features of new points, one row per point, such that their inner products with the rows of `factor` approximate the kernel.
+    pub fn transform(&self, x: &[T]) -> Result<Matrix, KernelError> {
+        let kxz = self
+            .kernel
+            .cross_covariance_matrix(&self.params, x, &self.landmarks)?;
+
+        Ok(multiply(&kxz, &self.projection))
+    }
+
+    /// `L L^T v`
+    pub fn matvec(&self, v: &[f64]) -> Result<Vec<f64>, KernelError> {
+        let (n, r) = (self.factor.rows(), self.factor.cols());
+        if v.len() != n {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let ltv = (0..r)
+            .map(|k| (0..n).map(|i| self.factor[(i, k)] * v[i]).sum::<f64>())
+            .collect::<Vec<f64>>();
+        let llv = (0..n)
+            .into_par_iter()
+            .map(|i| (0..r).map(|k| self.factor[(i, k)] * ltv[k]).sum())
+            .collect();
+
+        Ok(llv)
+    }
+
+    fn features(&self, x: &T) -> Result<Vec<f64>, KernelError> {
+        let kz = self
+            .landmarks
+            .iter()
+            .map(|z| self.kernel.value(&self.params, x, z))
+            .collect::<Result<Vec<f64>, KernelError>>()?;
+
+        Ok((0..self.rank())
+            .map(|k| {
+                kz.iter()
+                    .enumerate()
+                    .map(|(j, kz_j)| kz_j * self.projection[(j, k)])
+                    .sum()
+            })
+            .collect())
+    }
+}
+
+impl<K, T> PositiveDefiniteKernel<T> for Nystrom<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    fn params_len(&self) -> usize {
+        0
+    }
+
+    fn value(&self, params: &[f64], x: &T, xprime: &T) -> Result<f64, KernelError> {
+        if !params.is_empty() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+
+        let phi = self.features(x)?;
+        let phiprime = self.features(xprime)?;
+
+        Ok(phi.iter().zip(phiprime.iter()).map(|(a, b)| a * b).sum())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NystromBuilder {
+    landmarks: usize,
+    selection: LandmarkSelection,
+    seed: Option<u64>,
+}
+
+impl NystromBuilder {
+    /// Selects `landmarks` landmarks uniformly unless `selection` is given.
+    pub fn new(landmarks: usize) -> Self {
+        Self {
+            landmarks,
+            selection: LandmarkSelection::Uniform,
+            seed: None,
+        }
+    }
+
+    pub fn selection(mut self, selection: LandmarkSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Draws the landmarks from `StdRng` seeded with `seed`, so that `build` is reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build<K, T>(
+        self,
+        kernel: K,
+        params: &[f64],
+        x: &[T],
+    ) -> Result<Nystrom<K, T>, KernelError>
+    where
+        K: PositiveDefiniteKernel<T>,
+        T: Value,
+    {
+        match self.seed {
+            Some(seed) => self.build_with_rng(kernel, params, x, &mut StdRng::seed_from_u64(seed)),
+            None => self.build_with_rng(kernel, params, x, &mut rand::thread_rng()),
+        }
+    }
+
+    pub fn build_with_rng<K, T, R>(
+        self,
+        kernel: K,
+        params: &[f64],
+        x: &[T],
+        rng: &mut R,
+    ) -> Result<Nystrom<K, T>, KernelError>
+    where
+        K: PositiveDefiniteKernel<T>,
+        T: Value,
+        R: Rng + ?Sized,
+    {
+        if params.len() != kernel.params_len() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+        if self.landmarks == 0 || self.landmarks > x.len() {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let indices = match self.selection {
+            LandmarkSelection::Uniform => index::sample(rng, x.len(), self.landmarks).into_vec(),
+            LandmarkSelection::KMeansPlusPlus => self.kmeans_plus_plus(&kernel, params, x, rng)?,
+            LandmarkSelection::RidgeLeverageScore { lambda } => {
+                self.ridge_leverage_score(&kernel, params, x, lambda, rng)?
+            }
+        };
+        let landmarks = indices.iter().map(|&i| x[i].clone()).collect::<Vec<T>>();
+
+        let projection = pseudo_inverse_sqrt(&kernel.gram_matrix(params, &landmarks)?)?;
+        let kxz = kernel.cross_covariance_matrix(params, x, &landmarks)?;
+        let factor = multiply(&kxz, &projection);
+
+        Ok(Nystrom {
+            kernel,
+            params: params.to_vec(),
+            landmarks,
+            projection,
+            factor,
+        })
+    }
+
+    fn kmeans_plus_plus<K, T, R>(
+        &self,
+        kernel: &K,
+        params: &[f64],
+        x: &[T],
+        rng: &mut R,
+    ) -> Result<Vec<usize>, KernelError>
+    where
+        K: PositiveDefiniteKernel<T>,
+        T: Value,
+        R: Rng + ?Sized,
+    {
+        let diagonal = kernel.gram_diagonal(params, x)?.d().to_vec();
+        let mut distances = vec![f64::INFINITY; x.len()];
+        let mut indices = vec![rng.gen_range(0..x.len())];
+
+        while indices.len() < self.landmarks {
+            let last = indices[indices.len() - 1];
+            let kz = x
+                .par_iter()
+                .map(|xi| kernel.value(params, xi, &x[last]))
+                .collect::<Result<Vec<f64>, KernelError>>()?;
+            for (i, d) in distances.iter_mut().enumerate() {
+                *d = d.min((diagonal[i] + diagonal[last] - 2.0 * kz[i]).max(0.0));
+            }
+            for &i in indices.iter() {
+                distances[i] = 0.0;
+            }
+
+            match WeightedIndex::new(&distances) {
+                Ok(weights) => indices.push(weights.sample(rng)),
+                // The remaining points coincide with the landmarks in the feature space.
+                Err(_) => break,
+            }
+        }
+
+        Ok(indices)
+    }
+
+    fn ridge_leverage_score<K, T, R>(
+        &self,
+        kernel: &K,
+        params: &[f64],
+        x: &[T],
+        lambda: f64,
+        rng: &mut R,
+    ) -> Result<Vec<usize>, KernelError>
+    where
+        K: PositiveDefiniteKernel<T>,
+        T: Value,
+        R: Rng + ?Sized,
+    {
+        if lambda.is_nan() || lambda <= 0.0 {
+            return Err(KernelError::InvalidParameter);
+        }
+
+        let sketch = index::sample(rng, x.len(), self.landmarks)
+            .into_iter()
+            .map(|i| x[i].clone())
+            .collect::<Vec<T>>();
+        let (e, u) = symmetric_eigen(&kernel.gram_matrix(params, &sketch)?)?;
+        let kxs = kernel.cross_covariance_matrix(params, x, &sketch)?;
+        let diagonal = kernel.gram_diagonal(params, x)?;
+
+        // (k_ii - k_iS (K_SS + λ I)^-1 k_Si) / λ
+        let mut scores = (0..x.len())
+            .map(|i| {
+                let explained = (0..sketch.len())
+                    .map(|k| {
+                        let projected = (0..sketch.len())
+                            .map(|j| kxs[(i, j)] * u[(j, k)])
+                            .sum::<f64>();
+                        projected.powi(2) / (e[k].max(0.0) + lambda)
+                    })
+                    .sum::<f64>();
+                ((diagonal.d()[i] - explained) / lambda).max(0.0)
+            })
+            .collect::<Vec<f64>>();
+
+        let mut indices = Vec::with_capacity(self.landmarks);
+        while indices.len() < self.landmarks {
+            let i = match WeightedIndex::new(&scores) {
+                Ok(weights) => weights.sample(rng),
+                Err(_) => break,
+            };
+            scores[i] = 0.0;
+            indices.push(i);
+        }
+
+        Ok(indices)
+    }
+}
+
+/// `U Λ^-1/2` over the eigenvalues above the tolerance.
+fn pseudo_inverse_sqrt(kzz: &Matrix) -> Result<Matrix, KernelError> {
+    let (e, u) = symmetric_eigen(kzz)?;
+    let threshold = e.first().map_or(0.0, |e| e * RELATIVE_TOLERANCE);
+    let rank = e.iter().take_while(|&&e| e > threshold).count();
+
+    let mut projection = Matrix::new(kzz.rows(), rank);
+    for k in 0..rank {
+        let scale = e[k].sqrt().recip();
+        for j in 0..kzz.rows() {
+            projection[(j, k)] = u[(j, k)] * scale;
+        }
+    }
+
+    Ok(projection)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::opensrdk_linear_algebra::Matrix;
+    use crate::*;
+    use rand::prelude::*;
+
+    /// `||K - L L^T||_F / ||K||_F`
+    fn relative_error(k: &Matrix, l: &Matrix) -> f64 {
+        let n = k.rows();
+        let (error, norm) = (0..n * n)
+            .map(|index| {
+                let (i, j) = (index % n, index / n);
+                let approx = (0..l.cols()).map(|r| l[(i, r)] * l[(j, r)]).sum::<f64>();
+                ((approx - k[(i, j)]).powi(2), k[(i, j)].powi(2))
+            })
+            .fold((0.0, 0.0), |(e, n), (de, dn)| (e + de, n + dn));
+
+        (error / norm).sqrt()
+    }
+
+    #[test]
+    fn it_works() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let x = (0..60)
+            .map(|_| vec![rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)])
+            .collect::<Vec<Vec<f64>>>();
+        let kernel = RBF + Linear;
+        let params = [1.0, 0.5];
+        let k = kernel.gram_matrix(&params, &x).unwrap();
+
+        for selection in [
+            LandmarkSelection::Uniform,
+            LandmarkSelection::KMeansPlusPlus,
+            LandmarkSelection::RidgeLeverageScore { lambda: 1e-3 },
+        ] {
+            let nystrom = NystromBuilder::new(30)
+                .selection(selection.clone())
+                .seed(2)
+                .build(kernel.clone(), &params, &x)
+                .unwrap();
+            let exact = NystromBuilder::new(60)
+                .selection(selection)
+                .build(kernel.clone(), &params, &x)
+                .unwrap();
+
+            assert!(relative_error(&k, nystrom.factor()) < 0.01);
+            assert!(relative_error(&k, exact.factor()) < 1e-10);
+
+            let l = nystrom.factor();
+            let v = (0..60).map(|i| (i as f64).sin()).collect::<Vec<f64>>();
+            let kv = nystrom.matvec(&v).unwrap();
+            for i in 0..60 {
+                let expected = (0..60)
+                    .map(|j| (0..l.cols()).map(|r| l[(i, r)] * l[(j, r)]).sum::<f64>() * v[j])
+                    .sum::<f64>();
+                assert!((kv[i] - expected).abs() < 1e-10);
+            }
+
+            let phi = nystrom.transform(&x[0..2]).unwrap();
+            let approx = (0..nystrom.rank())
+                .map(|r| phi[(0, r)] * l[(1, r)])
+                .sum::<f64>();
+            assert!((approx - nystrom.value(&[], &x[0], &x[1]).unwrap()).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn it_works2() {
+        let x = vec![vec![0.0], vec![1.0]];
+
+        match NystromBuilder::new(3).build(RBF, &[1.0, 1.0], &x) {
+            Err(KernelError::InvalidArgument) => (),
+            _ => panic!(),
+        };
+
+        let repeated = [&x[..]; 5].concat();
+        let nystrom = NystromBuilder::new(4)
+            .selection(LandmarkSelection::KMeansPlusPlus)
+            .seed(1)
+            .build(RBF, &[1.0, 1.0], &repeated)
+            .unwrap();
+        assert_eq!(nystrom.landmarks().len(), 2);
+        assert_eq!(nystrom.rank(), 2);
+    }
+}
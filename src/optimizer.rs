@@ -0,0 +1,180 @@
+use crate::KernelError;
+
+/// Adam, used by the hyperparameter searches of the estimators.
+///
+/// The searches take the kernel params as they are, so wrap the kernel in `Transformed` to constrain them,
+/// and the positive hyperparameter of the estimator, such as `λ` or `σ²`, on the log scale.
+/// https://arxiv.org/abs/1412.6980
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Adam {
+    pub learning_rate: f64,
+    pub beta1: f64,
+    pub beta2: f64,
+    pub epsilon: f64,
+    pub iterations: usize,
+    /// Stops once the Euclidean norm of the gradient falls below this.
+    pub tolerance: f64,
+}
+
+impl Default for Adam {
+    fn default() -> Self {
+        Self {
+            learning_rate: 0.05,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+            iterations: 200,
+            tolerance: 1e-6,
+        }
+    }
+}
+
+impl Adam {
+    pub fn new(learning_rate: f64, iterations: usize) -> Self {
+        Self {
+            learning_rate,
+            iterations,
+            ..Default::default()
+        }
+    }
+
+    /// Minimizes `f`, which returns the objective and its gradient, starting from `x`.
+    /// Returns the point with the smallest objective among those evaluated, and the objective there.
+    /// A step to a point where `f` fails, such as a Gram matrix that is no longer positive definite,
+    /// is rejected and ends the search; only a failure at the starting point is returned as an error.
+    pub fn minimize<F>(&self, mut x: Vec<f64>, mut f: F) -> Result<(Vec<f64>, f64), KernelError>
+    where
+        F: FnMut(&[f64]) -> Result<(f64, Vec<f64>), KernelError>,
+    {
+        let mut m = vec![0.0; x.len()];
+        let mut v = vec![0.0; x.len()];
+        let mut best = (x.clone(), f64::INFINITY);
+
+        for t in 1..=self.iterations {
+            let (fx, gradient) = match f(&x) {
+                Ok(evaluation) => evaluation,
+                Err(_) if t > 1 => break,
+                Err(e) => return Err(e),
+            };
+            if gradient.len() != x.len() {
+                return Err(KernelError::ParametersLengthMismatch);
+            }
+            if fx < best.1 {
+                best = (x.clone(), fx);
+            }
+            if gradient.iter().map(|g| g.powi(2)).sum::<f64>().sqrt() < self.tolerance {
+                break;
+            }
+
+            let correction1 = 1.0 - self.beta1.powi(t as i32);
+            let correction2 = 1.0 - self.beta2.powi(t as i32);
+            for i in 0..x.len() {
+                m[i] = self.beta1 * m[i] + (1.0 - self.beta1) * gradient[i];
+                v[i] = self.beta2 * v[i] + (1.0 - self.beta2) * gradient[i].powi(2);
+                x[i] -= self.learning_rate * (m[i] / correction1)
+                    / ((v[i] / correction2).sqrt() + self.epsilon);
+            }
+        }
+
+        Ok(best)
+    }
+
+    /// `minimize` over `params` followed by the logarithm of `positive`. `f` takes the params and `positive`,
+    /// and returns the gradient with respect to the params and `ln positive`.
+    pub(crate) fn minimize_with_log_scale<F>(
+        &self,
+        params: &[f64],
+        positive: f64,
+        mut f: F,
+    ) -> Result<(Vec<f64>, f64), KernelError>
+    where
+        F: FnMut(&[f64], f64) -> Result<(f64, Vec<f64>), KernelError>,
+    {
+        if positive.is_nan() || positive <= 0.0 {
+            return Err(KernelError::InvalidParameter);
+        }
+
+        let initial = [params, &[positive.ln()]].concat();
+        let (best, _) = self.minimize(initial, |u| {
+            let (params, ln_positive) = u.split_at(u.len() - 1);
+            f(params, ln_positive[0].exp())
+        })?;
+        let (params, ln_positive) = best.split_at(best.len() - 1);
+
+        Ok((params.to_vec(), ln_positive[0].exp()))
+    }
+}
+
+/// Asserts `gradient`, with respect to `params` and `ln positive`, against central differences of `objective`.
+#[cfg(test)]
+pub(crate) fn assert_log_scale_gradient<F>(
+    gradient: &[f64],
+    params: &[f64],
+    positive: f64,
+    objective: F,
+) where
+    F: Fn(&[f64], f64) -> f64,
+{
+    let u = [params, &[positive.ln()]].concat();
+    assert_eq!(gradient.len(), u.len());
+
+    for j in 0..u.len() {
+        let objective = |h: f64| {
+            let mut u = u.clone();
+            u[j] += h;
+            let (params, ln_positive) = u.split_at(u.len() - 1);
+            objective(params, ln_positive[0].exp())
+        };
+        let numerical = (objective(1e-6) - objective(-1e-6)) / 2e-6;
+
+        assert!(
+            (gradient[j] - numerical).abs() < 1e-5 * numerical.abs().max(1.0),
+            "{} {} {}",
+            j,
+            gradient[j],
+            numerical
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let adam = Adam::new(0.1, 1000);
+
+        let (x, fx) = adam
+            .minimize(vec![3.0, -2.0], |x| {
+                let fx = (x[0] - 1.0).powi(2) + 2.0 * (x[1] + 0.5).powi(2);
+                Ok((fx, vec![2.0 * (x[0] - 1.0), 4.0 * (x[1] + 0.5)]))
+            })
+            .unwrap();
+
+        assert!((x[0] - 1.0).abs() < 1e-3);
+        assert!((x[1] + 0.5).abs() < 1e-3);
+        assert!(fx < 1e-5);
+    }
+
+    #[test]
+    fn it_works2() {
+        let adam = Adam::new(0.1, 1000);
+        let f = |x: &[f64]| {
+            if x[0] < 0.0 {
+                return Err(KernelError::NotPositiveDefinite);
+            }
+            Ok(((x[0] + 1.0).powi(2), vec![2.0 * (x[0] + 1.0)]))
+        };
+
+        let (x, fx) = adam.minimize(vec![1.0], f).unwrap();
+
+        assert!(x[0] >= 0.0 && x[0] < 0.2);
+        assert_eq!(fx, (x[0] + 1.0).powi(2));
+        match adam.minimize(vec![-1.0], f) {
+            Err(KernelError::NotPositiveDefinite) => (),
+            _ => panic!(),
+        };
+    }
+}
@@ -4,20 +4,22 @@ use crate::{
 };
 use opensrdk_linear_algebra::Vector;
 use rayon::prelude::*;
+use std::fmt::{self, Display, Formatter};
 use std::{ops::Add, ops::Mul};
 
 const PARAMS_LEN: usize = 2;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Periodic;
 
 impl Periodic {
     fn norm(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
         if params.len() != PARAMS_LEN {
-            return Err(KernelError::ParametersLengthMismatch.into());
+            return Err(KernelError::ParametersLengthMismatch);
         }
         if x.len() != xprime.len() {
-            return Err(KernelError::InvalidArgument.into());
+            return Err(KernelError::InvalidArgument);
         }
 
         let v = x
@@ -31,11 +33,24 @@ impl Periodic {
     }
 }
 
+impl Display for Periodic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Periodic")
+    }
+}
+
 impl PositiveDefiniteKernel<Vec<f64>> for Periodic {
     fn params_len(&self) -> usize {
         PARAMS_LEN
     }
 
+    fn params_names(&self) -> Vec<String> {
+        vec![
+            "periodic.amplitude".to_owned(),
+            "periodic.period".to_owned(),
+        ]
+    }
+
     fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
         let norm = self.norm(params, x, xprime)?;
 
@@ -74,8 +89,12 @@ impl ValueDifferentiableKernel<Vec<f64>> for Periodic {
         x: &Vec<f64>,
         xprime: &Vec<f64>,
     ) -> Result<Vec<f64>, KernelError> {
-        let value = &self.value(params, x, xprime)?;
-        let diff = (-value.sin() * 2.0 / params[1]
+        let norm = self.norm(params, x, xprime)?;
+        if norm == 0.0 {
+            return Ok(vec![0.0; x.len()]);
+        }
+
+        let diff = (-params[0] * (norm / params[1]).sin() / (params[1] * norm)
             * (x.clone().col_mat() - xprime.clone().col_mat()))
         .vec();
         Ok(diff)
@@ -89,9 +108,10 @@ impl ParamsDifferentiableKernel<Vec<f64>> for Periodic {
         x: &Vec<f64>,
         xprime: &Vec<f64>,
     ) -> Result<Vec<f64>, KernelError> {
-        let value = &self.value(params, x, xprime)?;
-        let diff0 = 1.0 / params[0];
-        let diff1 = value.sin() * 2.0 * params[1].powi(-2) * &self.norm(params, x, xprime)?;
+        let norm = self.norm(params, x, xprime)?;
+
+        let diff0 = (norm / params[1]).cos();
+        let diff1 = params[0] * (norm / params[1]).sin() * norm / params[1].powi(2);
         let diff = vec![diff0, diff1];
         Ok(diff)
     }
@@ -122,4 +142,9 @@ mod tests {
 
         assert_eq!(test_value, 1f64.exp());
     }
+
+    #[test]
+    fn check_gradients() {
+        assert_gradients(&Periodic, &[1.5, 0.8], 3);
+    }
 }
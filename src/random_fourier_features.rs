@@ -0,0 +1,325 @@
+use crate::{KernelAdd, KernelError, KernelMul, PositiveDefiniteKernel, StationaryKernel};
+use opensrdk_linear_algebra::Matrix;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+use rayon::prelude::*;
+use std::{ops::Add, ops::Mul};
+
+/// Feature map `φ(x) = sqrt(k(0) / M) [cos(ω_1 · x), sin(ω_1 · x), ..., cos(ω_M · x), sin(ω_M · x)]`
+/// with `M` frequencies `ω_m` drawn from the spectral density of a stationary kernel,
+/// so that `φ(x) · φ(x')` is an unbiased estimate of `k(x, x')`.
+///
+/// As a `PositiveDefiniteKernel` without params, it evaluates `φ(x) · φ(x')`.
+/// https://people.eecs.berkeley.edu/~brecht/papers/07.rah.rec.nips.pdf
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RandomFourierFeatures {
+    frequencies: Matrix,
+    variance: f64,
+}
+
+impl RandomFourierFeatures {
+    /// `features` is the dimension of `φ(x)` and must be even.
+    pub fn builder(dims: usize, features: usize) -> RandomFourierFeaturesBuilder {
+        RandomFourierFeaturesBuilder {
+            dims,
+            features,
+            orthogonal: false,
+            seed: None,
+        }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.frequencies.cols()
+    }
+
+    pub fn features(&self) -> usize {
+        2 * self.frequencies.rows()
+    }
+
+    /// `M x dims` matrix whose rows are the frequencies.
+    pub fn frequencies(&self) -> &Matrix {
+        &self.frequencies
+    }
+
+    pub fn transform(&self, x: &[f64]) -> Result<Vec<f64>, KernelError> {
+        if x.len() != self.dims() {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let m = self.frequencies.rows();
+        let scale = (self.variance / m as f64).sqrt();
+
+        let mut phi = Vec::with_capacity(2 * m);
+        for i in 0..m {
+            let arg = x
+                .iter()
+                .enumerate()
+                .map(|(j, x_j)| self.frequencies[(i, j)] * x_j)
+                .sum::<f64>();
+            phi.push(scale * arg.cos());
+            phi.push(scale * arg.sin());
+        }
+
+        Ok(phi)
+    }
+
+    /// Matrix with `x.len()` rows whose `i`-th row is `φ(x_i)`.
+    pub fn transform_matrix(&self, x: &[Vec<f64>]) -> Result<Matrix, KernelError> {
+        let phis = x
+            .par_iter()
+            .map(|xi| self.transform(xi))
+            .collect::<Result<Vec<Vec<f64>>, KernelError>>()?;
+
+        let mut phi = Matrix::new(x.len(), self.features());
+        for (i, phi_i) in phis.into_iter().enumerate() {
+            for (j, phi_ij) in phi_i.into_iter().enumerate() {
+                phi[(i, j)] = phi_ij;
+            }
+        }
+
+        Ok(phi)
+    }
+}
+
+impl PositiveDefiniteKernel<Vec<f64>> for RandomFourierFeatures {
+    fn params_len(&self) -> usize {
+        0
+    }
+
+    fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
+        if !params.is_empty() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+        if x.len() != self.dims() || xprime.len() != self.dims() {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let m = self.frequencies.rows();
+        let fx = (0..m)
+            .map(|i| {
+                x.iter()
+                    .zip(xprime.iter())
+                    .enumerate()
+                    .map(|(j, (x_j, xprime_j))| self.frequencies[(i, j)] * (x_j - xprime_j))
+                    .sum::<f64>()
+                    .cos()
+            })
+            .sum::<f64>()
+            * self.variance
+            / m as f64;
+
+        Ok(fx)
+    }
+}
+
+impl<R> Add<R> for RandomFourierFeatures
+where
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelAdd<Self, R, Vec<f64>>;
+
+    fn add(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<R> Mul<R> for RandomFourierFeatures
+where
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelMul<Self, R, Vec<f64>>;
+
+    fn mul(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RandomFourierFeaturesBuilder {
+    dims: usize,
+    features: usize,
+    orthogonal: bool,
+    seed: Option<u64>,
+}
+
+impl RandomFourierFeaturesBuilder {
+    /// Draws the frequencies in blocks of `dims` mutually orthogonal directions, with norms drawn from the
+    /// spectral density, which lowers the variance of the estimate. Only for isotropic kernels.
+    /// https://arxiv.org/abs/1610.09072
+    pub fn orthogonal(mut self) -> Self {
+        self.orthogonal = true;
+        self
+    }
+
+    /// Draws the frequencies from `StdRng` seeded with `seed`, so that `build` is reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build<K>(self, kernel: &K, params: &[f64]) -> Result<RandomFourierFeatures, KernelError>
+    where
+        K: StationaryKernel,
+    {
+        match self.seed {
+            Some(seed) => self.build_with_rng(kernel, params, &mut StdRng::seed_from_u64(seed)),
+            None => self.build_with_rng(kernel, params, &mut rand::thread_rng()),
+        }
+    }
+
+    pub fn build_with_rng<K, R>(
+        self,
+        kernel: &K,
+        params: &[f64],
+        rng: &mut R,
+    ) -> Result<RandomFourierFeatures, KernelError>
+    where
+        K: StationaryKernel,
+        R: Rng + ?Sized,
+    {
+        if self.dims == 0 || self.features == 0 || self.features % 2 == 1 {
+            return Err(KernelError::InvalidArgument);
+        }
+        if self.orthogonal && !kernel.is_isotropic() {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let m = self.features / 2;
+        let mut frequencies = Matrix::new(m, self.dims);
+        let mut i = 0;
+        while i < m {
+            let block = if self.orthogonal {
+                self.orthogonal_block(kernel, params, rng)?
+            } else {
+                vec![kernel.sample_frequency(params, self.dims, rng)?]
+            };
+            for omega in block.into_iter().take(m - i) {
+                for (j, omega_j) in omega.into_iter().enumerate() {
+                    frequencies[(i, j)] = omega_j;
+                }
+                i += 1;
+            }
+        }
+
+        Ok(RandomFourierFeatures {
+            frequencies,
+            variance: kernel.variance(params)?,
+        })
+    }
+
+    /// `dims` frequencies along the Gram–Schmidt orthonormalization of Gaussian directions.
+    fn orthogonal_block<K, R>(
+        &self,
+        kernel: &K,
+        params: &[f64],
+        rng: &mut R,
+    ) -> Result<Vec<Vec<f64>>, KernelError>
+    where
+        K: StationaryKernel,
+        R: Rng + ?Sized,
+    {
+        let mut directions: Vec<Vec<f64>> = Vec::with_capacity(self.dims);
+        while directions.len() < self.dims {
+            let mut u = (0..self.dims)
+                .map(|_| rng.sample::<f64, _>(StandardNormal))
+                .collect::<Vec<f64>>();
+            for e in directions.iter() {
+                let dot = u.iter().zip(e.iter()).map(|(u, e)| u * e).sum::<f64>();
+                u.iter_mut().zip(e.iter()).for_each(|(u, e)| *u -= dot * e);
+            }
+            let norm = u.iter().map(|u| u.powi(2)).sum::<f64>().sqrt();
+            if norm > 1e-8 {
+                directions.push(u.into_iter().map(|u| u / norm).collect());
+            }
+        }
+
+        directions
+            .into_iter()
+            .map(|e| {
+                let radius = kernel
+                    .sample_frequency(params, self.dims, rng)?
+                    .iter()
+                    .map(|w| w.powi(2))
+                    .sum::<f64>()
+                    .sqrt();
+                Ok(e.into_iter().map(|e| radius * e).collect())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let kernel = RBF;
+        let params = [1.5, 0.8];
+        let x = vec![0.1, 0.5, -0.3];
+        let xprime = vec![0.4, -0.2, 0.2];
+
+        let rff = RandomFourierFeatures::builder(3, 20000)
+            .seed(1)
+            .build(&kernel, &params)
+            .unwrap();
+        let phi = rff.transform(&x).unwrap();
+        let phiprime = rff.transform(&xprime).unwrap();
+        let approx = phi
+            .iter()
+            .zip(phiprime.iter())
+            .map(|(a, b)| a * b)
+            .sum::<f64>();
+        let expected = kernel.value(&params, &x, &xprime).unwrap();
+
+        assert_eq!(phi.len(), 20000);
+        assert!((approx - expected).abs() < 0.05, "{} {}", approx, expected);
+        assert!((rff.value(&[], &x, &xprime).unwrap() - approx).abs() < 1e-10);
+
+        let again = RandomFourierFeatures::builder(3, 20000)
+            .seed(1)
+            .build(&kernel, &params)
+            .unwrap();
+        assert_eq!(again.frequencies().elems(), rff.frequencies().elems());
+    }
+
+    #[test]
+    fn it_works2() {
+        let x = vec![0.1, 0.5];
+        let xprime = vec![0.4, -0.2];
+        let kernel = Matern::new(1.5);
+        let params = [1.2, 0.7];
+
+        let rff = RandomFourierFeatures::builder(2, 20000)
+            .orthogonal()
+            .seed(2)
+            .build(&kernel, &params)
+            .unwrap();
+        let w = rff.frequencies();
+        let dot = w[(0, 0)] * w[(1, 0)] + w[(0, 1)] * w[(1, 1)];
+        let expected = kernel.value(&params, &x, &xprime).unwrap();
+
+        assert!(dot.abs() < 1e-10);
+        assert!((rff.value(&[], &x, &xprime).unwrap() - expected).abs() < 0.05);
+
+        let kernel = SpectralMixture::new(2, 2);
+        let params = [0.6, 0.4, 0.3, 0.2, 0.5, 0.1, 0.5, 0.2, 0.1, 0.4];
+
+        let rff = RandomFourierFeatures::builder(2, 20000)
+            .seed(3)
+            .build(&kernel, &params)
+            .unwrap();
+        let expected = kernel.value(&params, &x, &xprime).unwrap();
+
+        assert!((rff.value(&[], &x, &xprime).unwrap() - expected).abs() < 0.05);
+        match RandomFourierFeatures::builder(2, 100)
+            .orthogonal()
+            .build(&kernel, &params)
+        {
+            Err(KernelError::InvalidArgument) => (),
+            _ => panic!(),
+        };
+    }
+}
@@ -0,0 +1,175 @@
+use super::PositiveDefiniteKernel;
+use crate::{
+    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, ValueDifferentiableKernel,
+};
+use rayon::prelude::*;
+use std::fmt::{self, Display, Formatter};
+use std::{ops::Add, ops::Mul};
+
+const PARAMS_LEN: usize = 3;
+
+/// Rational quadratic kernel with params `[variance, lengthscale, alpha]`.
+///
+/// `variance * (1 + r^2 / (2 * alpha * lengthscale^2))^(-alpha)`, which tends to `RBF` with params
+/// `[variance, 2 * lengthscale^2]` as `alpha` goes to infinity.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RationalQuadratic;
+
+impl RationalQuadratic {
+    /// `r^2 / (2 * alpha * lengthscale^2)`
+    fn scaled_norm_pow(
+        &self,
+        params: &[f64],
+        x: &Vec<f64>,
+        xprime: &Vec<f64>,
+    ) -> Result<f64, KernelError> {
+        if params.len() != PARAMS_LEN {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+        if x.len() != xprime.len() {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let norm_pow = x
+            .par_iter()
+            .zip(xprime.par_iter())
+            .map(|(x_i, xprime_i)| (x_i - xprime_i).powi(2))
+            .sum::<f64>();
+
+        Ok(norm_pow / (2.0 * params[2] * params[1].powi(2)))
+    }
+}
+
+impl Display for RationalQuadratic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "RationalQuadratic")
+    }
+}
+
+impl PositiveDefiniteKernel<Vec<f64>> for RationalQuadratic {
+    fn params_len(&self) -> usize {
+        PARAMS_LEN
+    }
+
+    fn params_names(&self) -> Vec<String> {
+        vec![
+            "rational_quadratic.variance".to_owned(),
+            "rational_quadratic.lengthscale".to_owned(),
+            "rational_quadratic.alpha".to_owned(),
+        ]
+    }
+
+    fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
+        let u = self.scaled_norm_pow(params, x, xprime)?;
+
+        let fx = params[0] * (1.0 + u).powf(-params[2]);
+
+        Ok(fx)
+    }
+}
+
+impl<R> Add<R> for RationalQuadratic
+where
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelAdd<Self, R, Vec<f64>>;
+
+    fn add(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<R> Mul<R> for RationalQuadratic
+where
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelMul<Self, R, Vec<f64>>;
+
+    fn mul(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl ValueDifferentiableKernel<Vec<f64>> for RationalQuadratic {
+    fn ln_diff_value(
+        &self,
+        params: &[f64],
+        x: &Vec<f64>,
+        xprime: &Vec<f64>,
+    ) -> Result<Vec<f64>, KernelError> {
+        let u = self.scaled_norm_pow(params, x, xprime)?;
+
+        let diff = x
+            .par_iter()
+            .zip(xprime.par_iter())
+            .map(|(x_i, xprime_i)| -(x_i - xprime_i) / ((1.0 + u) * params[1].powi(2)))
+            .collect::<Vec<f64>>();
+
+        Ok(diff)
+    }
+}
+
+impl ParamsDifferentiableKernel<Vec<f64>> for RationalQuadratic {
+    fn ln_diff_params(
+        &self,
+        params: &[f64],
+        x: &Vec<f64>,
+        xprime: &Vec<f64>,
+    ) -> Result<Vec<f64>, KernelError> {
+        let u = self.scaled_norm_pow(params, x, xprime)?;
+
+        let diff0 = 1.0 / params[0];
+        let diff1 = 2.0 * params[2] * u / ((1.0 + u) * params[1]);
+        let diff2 = -(1.0 + u).ln() + u / (1.0 + u);
+        let diff = vec![diff0, diff1, diff2];
+
+        Ok(diff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let kernel = RationalQuadratic;
+
+        let test_value = kernel
+            .value(&[1.0, 1.0, 1.0], &vec![1.0, 1.0, 0.0], &vec![0.0, 0.0, 0.0])
+            .unwrap();
+
+        assert_eq!(test_value, 0.5);
+    }
+
+    #[test]
+    fn it_works2() {
+        let kernel = RationalQuadratic;
+        let (variance, lengthscale) = (1.5, 0.7);
+        let x = vec![0.3, -0.2, 1.0];
+        let xprime = vec![-0.4, 0.5, 0.6];
+
+        let rbf = RBF
+            .value(&[variance, 2.0 * lengthscale * lengthscale], &x, &xprime)
+            .unwrap();
+        let errors = [1e0, 1e2, 1e4, 1e6]
+            .iter()
+            .map(|&alpha| {
+                (kernel
+                    .value(&[variance, lengthscale, alpha], &x, &xprime)
+                    .unwrap()
+                    - rbf)
+                    .abs()
+            })
+            .collect::<Vec<f64>>();
+
+        assert!(errors.windows(2).all(|e| e[1] < e[0]));
+        assert!(errors[3] < 1e-6);
+    }
+
+    #[test]
+    fn check_gradients() {
+        assert_gradients(&RationalQuadratic, &[1.5, 0.8, 2.5], 3);
+    }
+}
@@ -1,14 +1,19 @@
 use super::PositiveDefiniteKernel;
 use crate::{
-    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, ValueDifferentiableKernel,
+    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, StationaryKernel,
+    ValueDifferentiableKernel,
 };
 use opensrdk_linear_algebra::Vector;
+use rand::Rng;
+use rand_distr::StandardNormal;
 use rayon::prelude::*;
-use std::{ops::Add, ops::Mul};
+use std::fmt::{self, Display, Formatter};
+use std::{f64::consts::PI, ops::Add, ops::Mul};
 
 const PARAMS_LEN: usize = 2;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RBF;
 
 impl RBF {
@@ -19,10 +24,10 @@ impl RBF {
         xprime: &Vec<f64>,
     ) -> Result<f64, KernelError> {
         if params.len() != PARAMS_LEN {
-            return Err(KernelError::ParametersLengthMismatch.into());
+            return Err(KernelError::ParametersLengthMismatch);
         }
         if x.len() != xprime.len() {
-            return Err(KernelError::InvalidArgument.into());
+            return Err(KernelError::InvalidArgument);
         }
 
         let norm_pow = x
@@ -35,11 +40,21 @@ impl RBF {
     }
 }
 
+impl Display for RBF {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "RBF")
+    }
+}
+
 impl PositiveDefiniteKernel<Vec<f64>> for RBF {
     fn params_len(&self) -> usize {
         PARAMS_LEN
     }
 
+    fn params_names(&self) -> Vec<String> {
+        vec!["rbf.variance".to_owned(), "rbf.lengthscale".to_owned()]
+    }
+
     fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
         let norm_pow = self.norm_pow(params, x, xprime)?;
 
@@ -90,13 +105,58 @@ impl ParamsDifferentiableKernel<Vec<f64>> for RBF {
         x: &Vec<f64>,
         xprime: &Vec<f64>,
     ) -> Result<Vec<f64>, KernelError> {
+        let norm_pow = self.norm_pow(params, x, xprime)?;
+
         let diff0 = 1.0 / params[0];
-        let diff1 = 2.0 * params[1].powi(-2) * &self.norm_pow(params, x, xprime).unwrap();
+        let diff1 = norm_pow / params[1].powi(2);
         let diff = vec![diff0, diff1];
         Ok(diff)
     }
 }
 
+impl StationaryKernel for RBF {
+    fn variance(&self, params: &[f64]) -> Result<f64, KernelError> {
+        if params.len() != PARAMS_LEN {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+
+        Ok(params[0])
+    }
+
+    /// `N(0, 2 / lengthscale)` scaled by the variance.
+    fn spectral_density(&self, params: &[f64], omega: &[f64]) -> Result<f64, KernelError> {
+        let variance = self.variance(params)?;
+        let norm_pow = omega.iter().map(|w| w.powi(2)).sum::<f64>();
+
+        let s = variance
+            * (4.0 * PI / params[1]).powf(-(omega.len() as f64) / 2.0)
+            * (-norm_pow * params[1] / 4.0).exp();
+
+        Ok(s)
+    }
+
+    fn sample_frequency<R>(
+        &self,
+        params: &[f64],
+        dims: usize,
+        rng: &mut R,
+    ) -> Result<Vec<f64>, KernelError>
+    where
+        R: Rng + ?Sized,
+    {
+        self.variance(params)?;
+        let scale = (2.0 / params[1]).sqrt();
+
+        Ok((0..dims)
+            .map(|_| scale * rng.sample::<f64, _>(StandardNormal))
+            .collect())
+    }
+
+    fn is_isotropic(&self) -> bool {
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
@@ -134,4 +194,9 @@ mod tests {
 
         println!("{:?}", test_value);
     }
+
+    #[test]
+    fn check_gradients() {
+        assert_gradients(&RBF, &[1.5, 0.8], 3);
+    }
 }
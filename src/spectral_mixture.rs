@@ -1,12 +1,18 @@
 use super::PositiveDefiniteKernel;
 use crate::{
-    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, ValueDifferentiableKernel,
+    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, StationaryKernel,
+    ValueDifferentiableKernel,
 };
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use rand_distr::StandardNormal;
 use rayon::prelude::*;
+use std::fmt::{self, Display, Formatter};
 use std::{f64::consts::PI, ops::Add, ops::Mul};
 
 /// http://www.cs.cmu.edu/~andrewgw/andrewgwthesis.pdf
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpectralMixture {
     p: usize,
     q: usize,
@@ -16,6 +22,32 @@ impl SpectralMixture {
     pub fn new(p: usize, q: usize) -> Self {
         Self { p, q }
     }
+
+    /// Product over the dimensions of the `q`-th component, optionally leaving out dimension `except`.
+    fn factors_product(
+        &self,
+        v: &[f64],
+        mu: &[f64],
+        x: &[f64],
+        xprime: &[f64],
+        q: usize,
+        except: Option<usize>,
+    ) -> f64 {
+        (0..self.p)
+            .filter(|&p| Some(p) != except)
+            .map(|p| {
+                let d = x[p] - xprime[p];
+                (-2.0 * PI.powi(2) * d.powi(2) * v[self.p * q + p]).exp()
+                    * (2.0 * PI * d * mu[self.p * q + p]).cos()
+            })
+            .product()
+    }
+}
+
+impl Display for SpectralMixture {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "SpectralMixture({}, {})", self.p, self.q)
+    }
 }
 
 impl PositiveDefiniteKernel<Vec<f64>> for SpectralMixture {
@@ -23,15 +55,26 @@ impl PositiveDefiniteKernel<Vec<f64>> for SpectralMixture {
         self.q + self.p * self.q + self.p * self.q
     }
 
+    fn params_names(&self) -> Vec<String> {
+        let w = (0..self.q).map(|q| format!("spectral_mixture.weight.{}", q));
+        let v = (0..self.q).flat_map(|q| {
+            (0..self.p).map(move |p| format!("spectral_mixture.variance.{}.{}", q, p))
+        });
+        let mu = (0..self.q)
+            .flat_map(|q| (0..self.p).map(move |p| format!("spectral_mixture.mean.{}.{}", q, p)));
+
+        w.chain(v).chain(mu).collect()
+    }
+
     fn value(&self, params: &[f64], x: &Vec<f64>, xprime: &Vec<f64>) -> Result<f64, KernelError> {
         if params.len() != self.params_len() {
-            return Err(KernelError::ParametersLengthMismatch.into());
+            return Err(KernelError::ParametersLengthMismatch);
         }
         if self.p != x.len() {
-            return Err(KernelError::ParametersLengthMismatch.into());
+            return Err(KernelError::ParametersLengthMismatch);
         }
         if x.len() != xprime.len() {
-            return Err(KernelError::InvalidArgument.into());
+            return Err(KernelError::InvalidArgument);
         }
 
         let w = &params[0..self.q];
@@ -44,8 +87,8 @@ impl PositiveDefiniteKernel<Vec<f64>> for SpectralMixture {
                 w[q] * (0..self.p)
                     .into_par_iter()
                     .map(|p| {
-                        (-2.0 * PI.powi(2) * (x[p] - xprime[p]).powi(2) * v[self.p * p + q]).exp()
-                            * (2.0 * PI * (x[p] - xprime[p]) * mu[self.p * p + q]).cos()
+                        (-2.0 * PI.powi(2) * (x[p] - xprime[p]).powi(2) * v[self.p * q + p]).exp()
+                            * (2.0 * PI * (x[p] - xprime[p]) * mu[self.p * q + p]).cos()
                     })
                     .product::<f64>()
             })
@@ -84,7 +127,7 @@ impl ValueDifferentiableKernel<Vec<f64>> for SpectralMixture {
         x: &Vec<f64>,
         xprime: &Vec<f64>,
     ) -> Result<Vec<f64>, KernelError> {
-        let value = self.value(params, x, xprime).unwrap();
+        let value = self.value(params, x, xprime)?;
         let w = &params[0..self.q];
         let v = &params[self.q..self.q + self.p * self.q];
         let mu = &params[self.q + self.p * self.q..self.q + self.p * self.q + self.p * self.q];
@@ -93,26 +136,17 @@ impl ValueDifferentiableKernel<Vec<f64>> for SpectralMixture {
             .into_par_iter()
             .map(|p| {
                 (0..self.q)
-                    .into_par_iter()
                     .map(|q| {
-                        let each_wd = w[q]
-                            * (0..self.p)
-                                .into_par_iter()
-                                .map(|i| {
-                                    (-2.0
-                                        * PI.powi(2)
-                                        * (x[i] - xprime[i]).powi(2)
-                                        * v[self.p * i + q])
-                                        .exp()
-                                        * (2.0 * PI * (x[i] - xprime[i]) * mu[self.p * i + q]).cos()
-                                })
-                                .product::<f64>();
-                        let diff_d = (4.0 * PI.powi(2) * (x[p] - xprime[p]) * v[self.p * p + q])
-                            + (2.0 * PI * (x[p] - xprime[p]) * mu[self.p * p + q]).tan()
-                                * ((-2.0) * PI * (x[p] - xprime[p]) * mu[self.p * p + q]);
-                        diff_d * each_wd / value
+                        let d = x[p] - xprime[p];
+                        let e = (-2.0 * PI.powi(2) * d.powi(2) * v[self.p * q + p]).exp();
+                        let arg = 2.0 * PI * d * mu[self.p * q + p];
+                        let diff_d = e
+                            * (-4.0 * PI.powi(2) * d * v[self.p * q + p] * arg.cos()
+                                - 2.0 * PI * mu[self.p * q + p] * arg.sin());
+                        w[q] * self.factors_product(v, mu, x, xprime, q, Some(p)) * diff_d
                     })
-                    .sum()
+                    .sum::<f64>()
+                    / value
             })
             .collect::<Vec<f64>>();
 
@@ -127,65 +161,36 @@ impl ParamsDifferentiableKernel<Vec<f64>> for SpectralMixture {
         x: &Vec<f64>,
         xprime: &Vec<f64>,
     ) -> Result<Vec<f64>, KernelError> {
-        let value = self.value(params, x, xprime).unwrap();
+        let value = self.value(params, x, xprime)?;
         let w = &params[0..self.q];
         let v = &params[self.q..self.q + self.p * self.q];
         let mu = &params[self.q + self.p * self.q..self.q + self.p * self.q + self.p * self.q];
 
         let diff_w = (0..self.q)
             .into_par_iter()
-            .map(|q| {
-                (0..self.p)
-                    .into_par_iter()
-                    .map(|i| {
-                        (-2.0 * PI.powi(2) * (x[i] - xprime[i]).powi(2) * v[self.p * i + q]).exp()
-                            * (2.0 * PI * (x[i] - xprime[i]) * mu[self.p * i + q]).cos()
-                    })
-                    .product::<f64>()
-            })
+            .map(|q| self.factors_product(v, mu, x, xprime, q, None) / value)
             .collect::<Vec<f64>>();
 
-        let diff_mu = (0..self.q)
+        let diff_v = (0..self.q)
             .into_par_iter()
             .map(|q| {
-                let each_wd = w[q]
-                    * (0..self.p)
-                        .into_par_iter()
-                        .map(|i| {
-                            (-2.0 * PI.powi(2) * (x[i] - xprime[i]).powi(2) * v[self.p * i + q])
-                                .exp()
-                                * (2.0 * PI * (x[i] - xprime[i]) * mu[self.p * i + q]).cos()
-                        })
-                        .product::<f64>();
+                let each_wd = w[q] * self.factors_product(v, mu, x, xprime, q, None);
                 (0..self.p)
-                    .into_par_iter()
-                    .map(|p| {
-                        let diff_d = (2.0 * PI * (x[p] - xprime[p]) * mu[self.p * p + q]).tan()
-                            * ((-2.0) * PI * (x[p] - xprime[p]));
-                        diff_d * each_wd / value
-                    })
+                    .map(|p| -2.0 * PI.powi(2) * (x[p] - xprime[p]).powi(2) * each_wd / value)
                     .collect::<Vec<f64>>()
             })
             .collect::<Vec<Vec<f64>>>()
             .concat();
 
-        let diff_v = (0..self.q)
+        let diff_mu = (0..self.q)
             .into_par_iter()
             .map(|q| {
-                let each_wd = w[q]
-                    * (0..self.p)
-                        .into_par_iter()
-                        .map(|i| {
-                            (-2.0 * PI.powi(2) * (x[i] - xprime[i]).powi(2) * v[self.p * i + q])
-                                .exp()
-                                * (2.0 * PI * (x[i] - xprime[i]) * mu[self.p * i + q]).cos()
-                        })
-                        .product::<f64>();
                 (0..self.p)
-                    .into_par_iter()
                     .map(|p| {
-                        let diff_d = 4.0 * PI.powi(2) * (x[p] - xprime[p]).powi(2);
-                        diff_d * each_wd / value
+                        let d = x[p] - xprime[p];
+                        let e = (-2.0 * PI.powi(2) * d.powi(2) * v[self.p * q + p]).exp();
+                        let diff_d = -2.0 * PI * d * e * (2.0 * PI * d * mu[self.p * q + p]).sin();
+                        w[q] * self.factors_product(v, mu, x, xprime, q, Some(p)) * diff_d / value
                     })
                     .collect::<Vec<f64>>()
             })
@@ -198,6 +203,77 @@ impl ParamsDifferentiableKernel<Vec<f64>> for SpectralMixture {
     }
 }
 
+impl StationaryKernel for SpectralMixture {
+    fn variance(&self, params: &[f64]) -> Result<f64, KernelError> {
+        if params.len() != self.params_len() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+
+        Ok(params[0..self.q].iter().sum())
+    }
+
+    /// Mixture over the components of the products over the dimensions of
+    /// `(N(2π mean, 4π² variance) + N(-2π mean, 4π² variance)) / 2`, weighted by the weights.
+    fn spectral_density(&self, params: &[f64], omega: &[f64]) -> Result<f64, KernelError> {
+        self.variance(params)?;
+        if omega.len() != self.p {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let w = &params[0..self.q];
+        let v = &params[self.q..self.q + self.p * self.q];
+        let mu = &params[self.q + self.p * self.q..self.q + self.p * self.q + self.p * self.q];
+        let normal = |x: f64, mean: f64, variance: f64| {
+            (-(x - mean).powi(2) / (2.0 * variance)).exp() / (2.0 * PI * variance).sqrt()
+        };
+
+        let s = (0..self.q)
+            .map(|q| {
+                w[q] * (0..self.p)
+                    .map(|p| {
+                        let mean = 2.0 * PI * mu[self.p * q + p];
+                        let variance = 4.0 * PI.powi(2) * v[self.p * q + p];
+                        (normal(omega[p], mean, variance) + normal(omega[p], -mean, variance)) / 2.0
+                    })
+                    .product::<f64>()
+            })
+            .sum();
+
+        Ok(s)
+    }
+
+    fn sample_frequency<R>(
+        &self,
+        params: &[f64],
+        dims: usize,
+        rng: &mut R,
+    ) -> Result<Vec<f64>, KernelError>
+    where
+        R: Rng + ?Sized,
+    {
+        self.variance(params)?;
+        if dims != self.p {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let w = &params[0..self.q];
+        let v = &params[self.q..self.q + self.p * self.q];
+        let mu = &params[self.q + self.p * self.q..self.q + self.p * self.q + self.p * self.q];
+        let q = WeightedIndex::new(w)
+            .map_err(|_| KernelError::InvalidParameter)?
+            .sample(rng);
+
+        Ok((0..self.p)
+            .map(|p| {
+                let sign = if rng.gen::<bool>() { 1.0 } else { -1.0 };
+                let s = sign * mu[self.p * q + p]
+                    + v[self.p * q + p].sqrt() * rng.sample::<f64, _>(StandardNormal);
+                2.0 * PI * s
+            })
+            .collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
@@ -212,4 +288,23 @@ mod tests {
             _ => panic!(),
         };
     }
+
+    #[test]
+    fn check_gradients() {
+        let params = [
+            1.0, 0.6, 1.4, 0.3, 0.8, 0.5, 0.2, 0.9, 0.4, 0.1, 0.05, 0.15, 0.0, 0.2, 0.12,
+        ];
+        assert_gradients(&SpectralMixture::new(2, 3), &params, 2);
+    }
+
+    #[test]
+    fn it_works2() {
+        let kernel = SpectralMixture::new(2, 3);
+        let names = kernel.params_names();
+
+        assert_eq!(names.len(), kernel.params_len());
+        assert_eq!(names[2], "spectral_mixture.weight.2");
+        assert_eq!(names[4], "spectral_mixture.variance.0.1");
+        assert_eq!(names[9], "spectral_mixture.mean.0.0");
+    }
 }
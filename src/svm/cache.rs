@@ -0,0 +1,79 @@
+use crate::{KernelError, PositiveDefiniteKernel, Value};
+use rayon::prelude::*;
+use std::rc::Rc;
+
+/// Least recently used cache of the rows `K(x_i, X)` of the Gram matrix.
+pub(crate) struct KernelCache<'a, K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    kernel: &'a K,
+    params: &'a [f64],
+    x: &'a [T],
+    rows: Vec<Option<Rc<Vec<f64>>>>,
+    last_used: Vec<u64>,
+    clock: u64,
+    cached: usize,
+    capacity: usize,
+    diagonal: Vec<f64>,
+}
+
+impl<'a, K, T> KernelCache<'a, K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    pub(crate) fn new(
+        kernel: &'a K,
+        params: &'a [f64],
+        x: &'a [T],
+        capacity: usize,
+    ) -> Result<Self, KernelError> {
+        let diagonal = kernel.gram_diagonal(params, x)?.d().to_vec();
+
+        Ok(Self {
+            kernel,
+            params,
+            x,
+            rows: vec![None; x.len()],
+            last_used: vec![0; x.len()],
+            clock: 0,
+            cached: 0,
+            capacity: capacity.max(2),
+            diagonal,
+        })
+    }
+
+    pub(crate) fn diagonal(&self) -> &[f64] {
+        &self.diagonal
+    }
+
+    pub(crate) fn row(&mut self, i: usize) -> Result<Rc<Vec<f64>>, KernelError> {
+        self.clock += 1;
+        self.last_used[i] = self.clock;
+        if let Some(row) = &self.rows[i] {
+            return Ok(row.clone());
+        }
+
+        if self.cached == self.capacity {
+            let oldest = (0..self.rows.len())
+                .filter(|&j| self.rows[j].is_some())
+                .min_by_key(|&j| self.last_used[j])
+                .unwrap_or(i);
+            self.rows[oldest] = None;
+            self.cached -= 1;
+        }
+
+        let (kernel, params, x) = (self.kernel, self.params, self.x);
+        let row = Rc::new(
+            x.par_iter()
+                .map(|xj| kernel.value(params, &x[i], xj))
+                .collect::<Result<Vec<f64>, KernelError>>()?,
+        );
+        self.rows[i] = Some(row.clone());
+        self.cached += 1;
+
+        Ok(row)
+    }
+}
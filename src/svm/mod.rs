@@ -0,0 +1,30 @@
+pub mod svc;
+pub mod svr;
+
+mod cache;
+mod solver;
+
+/// Settings of the SMO solver shared by `SupportVectorClassifier` and `SupportVectorRegressor`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SmoOptions {
+    /// Stops once the maximal violation of the KKT conditions falls below this.
+    pub tolerance: f64,
+    /// Number of kernel rows kept in the least recently used cache, at least 2.
+    pub cache_rows: usize,
+    /// Temporarily removes variables that are likely to stay at a bound from the working-set selection.
+    pub shrinking: bool,
+    /// Gives up after this many iterations, which the models report through `converged`.
+    pub max_iterations: usize,
+}
+
+impl Default for SmoOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: 1e-3,
+            cache_rows: 1000,
+            shrinking: true,
+            max_iterations: 10_000_000,
+        }
+    }
+}
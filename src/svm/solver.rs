@@ -0,0 +1,391 @@
+use super::cache::KernelCache;
+use super::SmoOptions;
+use crate::{KernelError, PositiveDefiniteKernel, Value};
+
+/// Substitute for non-positive curvature along the working-set direction.
+const TAU: f64 = 1e-12;
+
+/// Dual problem `min α^T Q α / 2 + p^T α` subject to `y^T α = 0` and `0 <= α_t <= c_t`,
+/// with `Q_st = y_s y_t K(x_index[s], x_index[t])` and `y_t = ±1`.
+pub(crate) struct Problem {
+    pub(crate) index: Vec<usize>,
+    pub(crate) y: Vec<f64>,
+    pub(crate) p: Vec<f64>,
+    pub(crate) c: Vec<f64>,
+}
+
+/// `α` and `ρ` at the optimum, so that the decision function is `Σ_t y_t α_t K(x_index[t], x) - ρ`.
+pub(crate) struct Solution {
+    pub(crate) alpha: Vec<f64>,
+    pub(crate) rho: f64,
+    /// Whether the optimum was reached within `max_iterations`; otherwise `α` is the last iterate.
+    pub(crate) converged: bool,
+}
+
+/// Sequential minimal optimization with the second-order working-set selection and shrinking of LIBSVM.
+/// https://www.csie.ntu.edu.tw/~cjlin/papers/libsvm.pdf
+/// https://www.jmlr.org/papers/volume6/fan05a/fan05a.pdf
+struct Solver<'a, K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    problem: Problem,
+    cache: KernelCache<'a, K, T>,
+    alpha: Vec<f64>,
+    /// Gradient `Q α + p`.
+    g: Vec<f64>,
+    /// `Σ_{t: α_t = c_t} c_t Q_st`, to reconstruct the gradient of shrunk variables.
+    g_bar: Vec<f64>,
+    active: Vec<usize>,
+    unshrink: bool,
+}
+
+pub(crate) fn solve<K, T>(
+    kernel: &K,
+    params: &[f64],
+    x: &[T],
+    problem: Problem,
+    options: &SmoOptions,
+) -> Result<Solution, KernelError>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    let l = problem.y.len();
+    let mut solver = Solver {
+        cache: KernelCache::new(kernel, params, x, options.cache_rows)?,
+        alpha: vec![0.0; l],
+        g: problem.p.clone(),
+        g_bar: vec![0.0; l],
+        active: (0..l).collect(),
+        unshrink: false,
+        problem,
+    };
+
+    let mut counter = l.min(1000) + 1;
+    let mut converged = false;
+    for _ in 0..options.max_iterations {
+        counter -= 1;
+        if counter == 0 {
+            counter = l.min(1000);
+            if options.shrinking {
+                solver.shrink(options.tolerance)?;
+            }
+        }
+
+        let (i, j) = match solver.select_working_set(options.tolerance)? {
+            Some(pair) => pair,
+            None => {
+                solver.reconstruct_gradient()?;
+                solver.active = (0..l).collect();
+                match solver.select_working_set(options.tolerance)? {
+                    Some(pair) => {
+                        counter = 1;
+                        pair
+                    }
+                    None => {
+                        converged = true;
+                        break;
+                    }
+                }
+            }
+        };
+
+        solver.update(i, j)?;
+    }
+
+    solver.reconstruct_gradient()?;
+    solver.active = (0..l).collect();
+    let rho = solver.rho();
+
+    Ok(Solution {
+        alpha: solver.alpha,
+        rho,
+        converged,
+    })
+}
+
+impl<'a, K, T> Solver<'a, K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    fn is_upper_bound(&self, t: usize) -> bool {
+        self.alpha[t] >= self.problem.c[t]
+    }
+
+    fn is_lower_bound(&self, t: usize) -> bool {
+        self.alpha[t] <= 0.0
+    }
+
+    fn qd(&self, t: usize) -> f64 {
+        self.cache.diagonal()[self.problem.index[t]]
+    }
+
+    /// The `s`-th row of `Q`.
+    fn q_row(&mut self, s: usize) -> Result<Vec<f64>, KernelError> {
+        let k = self.cache.row(self.problem.index[s])?;
+        let (index, y) = (&self.problem.index, &self.problem.y);
+
+        Ok((0..y.len()).map(|t| y[s] * y[t] * k[index[t]]).collect())
+    }
+
+    /// Maximal violating pair by the second-order rule, or `None` at the optimum within `tolerance`.
+    fn select_working_set(
+        &mut self,
+        tolerance: f64,
+    ) -> Result<Option<(usize, usize)>, KernelError> {
+        let y = &self.problem.y;
+        let mut g_max = f64::NEG_INFINITY;
+        let mut g_max2 = f64::NEG_INFINITY;
+        let mut i = None;
+        for &t in self.active.iter() {
+            if y[t] > 0.0 {
+                if !self.is_upper_bound(t) && -self.g[t] >= g_max {
+                    g_max = -self.g[t];
+                    i = Some(t);
+                }
+            } else if !self.is_lower_bound(t) && self.g[t] >= g_max {
+                g_max = self.g[t];
+                i = Some(t);
+            }
+        }
+        let i = match i {
+            Some(i) => i,
+            None => return Ok(None),
+        };
+
+        let q_i = self.q_row(i)?;
+        let y = &self.problem.y;
+        let mut j = None;
+        let mut obj_diff_min = f64::INFINITY;
+        for &t in self.active.iter() {
+            let (grad_diff, quad_coef) = if y[t] > 0.0 {
+                if self.is_lower_bound(t) {
+                    continue;
+                }
+                g_max2 = g_max2.max(self.g[t]);
+                (
+                    g_max + self.g[t],
+                    self.qd(i) + self.qd(t) - 2.0 * y[i] * q_i[t],
+                )
+            } else {
+                if self.is_upper_bound(t) {
+                    continue;
+                }
+                g_max2 = g_max2.max(-self.g[t]);
+                (
+                    g_max - self.g[t],
+                    self.qd(i) + self.qd(t) + 2.0 * y[i] * q_i[t],
+                )
+            };
+
+            if grad_diff > 0.0 {
+                let obj_diff = -grad_diff.powi(2) / if quad_coef > 0.0 { quad_coef } else { TAU };
+                if obj_diff <= obj_diff_min {
+                    obj_diff_min = obj_diff;
+                    j = Some(t);
+                }
+            }
+        }
+
+        if g_max + g_max2 < tolerance {
+            return Ok(None);
+        }
+
+        Ok(j.map(|j| (i, j)))
+    }
+
+    /// Solves the two-variable subproblem in `(α_i, α_j)` and updates the gradients.
+    fn update(&mut self, i: usize, j: usize) -> Result<(), KernelError> {
+        let q_i = self.q_row(i)?;
+        let q_j = self.q_row(j)?;
+        let (c_i, c_j) = (self.problem.c[i], self.problem.c[j]);
+        let (old_alpha_i, old_alpha_j) = (self.alpha[i], self.alpha[j]);
+        let (was_upper_i, was_upper_j) = (self.is_upper_bound(i), self.is_upper_bound(j));
+        let (mut alpha_i, mut alpha_j) = (old_alpha_i, old_alpha_j);
+
+        if self.problem.y[i] != self.problem.y[j] {
+            let quad_coef = (self.qd(i) + self.qd(j) + 2.0 * q_i[j]).max(TAU);
+            let delta = (-self.g[i] - self.g[j]) / quad_coef;
+            let diff = alpha_i - alpha_j;
+            alpha_i += delta;
+            alpha_j += delta;
+
+            if diff > 0.0 {
+                if alpha_j < 0.0 {
+                    alpha_j = 0.0;
+                    alpha_i = diff;
+                }
+            } else if alpha_i < 0.0 {
+                alpha_i = 0.0;
+                alpha_j = -diff;
+            }
+            if diff > c_i - c_j {
+                if alpha_i > c_i {
+                    alpha_i = c_i;
+                    alpha_j = c_i - diff;
+                }
+            } else if alpha_j > c_j {
+                alpha_j = c_j;
+                alpha_i = c_j + diff;
+            }
+        } else {
+            let quad_coef = (self.qd(i) + self.qd(j) - 2.0 * q_i[j]).max(TAU);
+            let delta = (self.g[i] - self.g[j]) / quad_coef;
+            let sum = alpha_i + alpha_j;
+            alpha_i -= delta;
+            alpha_j += delta;
+
+            if sum > c_i {
+                if alpha_i > c_i {
+                    alpha_i = c_i;
+                    alpha_j = sum - c_i;
+                }
+            } else if alpha_j < 0.0 {
+                alpha_j = 0.0;
+                alpha_i = sum;
+            }
+            if sum > c_j {
+                if alpha_j > c_j {
+                    alpha_j = c_j;
+                    alpha_i = sum - c_j;
+                }
+            } else if alpha_i < 0.0 {
+                alpha_i = 0.0;
+                alpha_j = sum;
+            }
+        }
+
+        self.alpha[i] = alpha_i;
+        self.alpha[j] = alpha_j;
+        let (delta_i, delta_j) = (alpha_i - old_alpha_i, alpha_j - old_alpha_j);
+        for &t in self.active.iter() {
+            self.g[t] += q_i[t] * delta_i + q_j[t] * delta_j;
+        }
+
+        for (s, q_s, c_s, was_upper) in [(i, &q_i, c_i, was_upper_i), (j, &q_j, c_j, was_upper_j)] {
+            let is_upper = self.is_upper_bound(s);
+            if was_upper != is_upper {
+                let sign = if is_upper { 1.0 } else { -1.0 };
+                for (g_bar, q) in self.g_bar.iter_mut().zip(q_s.iter()) {
+                    *g_bar += sign * c_s * q;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Recomputes the gradient of the shrunk variables from `g_bar` and the free variables.
+    fn reconstruct_gradient(&mut self) -> Result<(), KernelError> {
+        let l = self.alpha.len();
+        if self.active.len() == l {
+            return Ok(());
+        }
+
+        let mut is_active = vec![false; l];
+        for &t in self.active.iter() {
+            is_active[t] = true;
+        }
+        let inactive = (0..l).filter(|&t| !is_active[t]).collect::<Vec<usize>>();
+        for &t in inactive.iter() {
+            self.g[t] = self.g_bar[t] + self.problem.p[t];
+        }
+
+        for s in 0..l {
+            if self.is_lower_bound(s) || self.is_upper_bound(s) {
+                continue;
+            }
+            let q_s = self.q_row(s)?;
+            for &t in inactive.iter() {
+                self.g[t] += self.alpha[s] * q_s[t];
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the variables at a bound whose gradient keeps them there.
+    fn shrink(&mut self, tolerance: f64) -> Result<(), KernelError> {
+        let y = &self.problem.y;
+        let mut g_max1 = f64::NEG_INFINITY;
+        let mut g_max2 = f64::NEG_INFINITY;
+        for &t in self.active.iter() {
+            let (up, low) = if y[t] > 0.0 {
+                (&mut g_max1, &mut g_max2)
+            } else {
+                (&mut g_max2, &mut g_max1)
+            };
+            if !self.is_upper_bound(t) {
+                *up = up.max(-self.g[t]);
+            }
+            if !self.is_lower_bound(t) {
+                *low = low.max(self.g[t]);
+            }
+        }
+
+        if !self.unshrink && g_max1 + g_max2 <= tolerance * 10.0 {
+            self.unshrink = true;
+            self.reconstruct_gradient()?;
+            self.active = (0..self.alpha.len()).collect();
+        }
+
+        let active = std::mem::take(&mut self.active);
+        self.active = active
+            .into_iter()
+            .filter(|&t| {
+                let y_t = self.problem.y[t];
+                if self.is_upper_bound(t) {
+                    -self.g[t] <= if y_t > 0.0 { g_max1 } else { g_max2 }
+                } else if self.is_lower_bound(t) {
+                    self.g[t] <= if y_t > 0.0 { g_max2 } else { g_max1 }
+                } else {
+                    true
+                }
+            })
+            .collect();
+
+        Ok(())
+    }
+
+    /// `ρ` from the average of `y_t g_t` over the free variables, or the middle of the feasible interval,
+    /// or its finite end when only one side is bounded.
+    fn rho(&self) -> f64 {
+        let y = &self.problem.y;
+        let mut upper = f64::INFINITY;
+        let mut lower = f64::NEG_INFINITY;
+        let mut sum_free = 0.0;
+        let mut free = 0;
+        for (t, &y_t) in y.iter().enumerate() {
+            let yg = y_t * self.g[t];
+            if self.is_upper_bound(t) {
+                if y_t < 0.0 {
+                    upper = upper.min(yg);
+                } else {
+                    lower = lower.max(yg);
+                }
+            } else if self.is_lower_bound(t) {
+                if y_t > 0.0 {
+                    upper = upper.min(yg);
+                } else {
+                    lower = lower.max(yg);
+                }
+            } else {
+                free += 1;
+                sum_free += yg;
+            }
+        }
+
+        if free > 0 {
+            sum_free / free as f64
+        } else if upper.is_infinite() {
+            lower
+        } else if lower.is_infinite() {
+            upper
+        } else {
+            (upper + lower) / 2.0
+        }
+    }
+}
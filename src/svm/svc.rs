@@ -0,0 +1,247 @@
+use super::solver::{solve, Problem};
+use super::SmoOptions;
+use crate::{KernelError, PositiveDefiniteKernel, Value};
+use rayon::prelude::*;
+
+/// C-support vector classification with labels `±1`, solved by SMO.
+///
+/// The decision function is `Σ_i coef_i k(sv_i, x) + b` over the support vectors, with `coef_i = y_i α_i`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SupportVectorClassifier<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    kernel: K,
+    params: Vec<f64>,
+    support_indices: Vec<usize>,
+    support_vectors: Vec<T>,
+    dual_coefficients: Vec<f64>,
+    intercept: f64,
+    converged: bool,
+}
+
+impl<K, T> SupportVectorClassifier<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    /// `y` needs both labels.
+    pub fn fit(
+        kernel: K,
+        params: &[f64],
+        x: Vec<T>,
+        y: &[f64],
+        c: f64,
+        options: &SmoOptions,
+    ) -> Result<Self, KernelError> {
+        if x.len() != y.len() || y.iter().any(|&y| y != 1.0 && y != -1.0) {
+            return Err(KernelError::InvalidArgument);
+        }
+        if !y.contains(&1.0) || !y.contains(&-1.0) {
+            return Err(KernelError::InvalidArgument);
+        }
+        if c.is_nan() || c <= 0.0 {
+            return Err(KernelError::InvalidParameter);
+        }
+
+        let l = x.len();
+        let problem = Problem {
+            index: (0..l).collect(),
+            y: y.to_vec(),
+            p: vec![-1.0; l],
+            c: vec![c; l],
+        };
+        let solution = solve(&kernel, params, &x, problem, options)?;
+
+        let support_indices = (0..l)
+            .filter(|&i| solution.alpha[i] > 0.0)
+            .collect::<Vec<usize>>();
+
+        Ok(Self {
+            support_vectors: support_indices.iter().map(|&i| x[i].clone()).collect(),
+            dual_coefficients: support_indices
+                .iter()
+                .map(|&i| y[i] * solution.alpha[i])
+                .collect(),
+            support_indices,
+            intercept: -solution.rho,
+            converged: solution.converged,
+            kernel,
+            params: params.to_vec(),
+        })
+    }
+
+    pub fn kernel_ref(&self) -> &K {
+        &self.kernel
+    }
+
+    pub fn params(&self) -> &[f64] {
+        &self.params
+    }
+
+    /// Indices of the support vectors in the training set.
+    pub fn support_indices(&self) -> &[usize] {
+        &self.support_indices
+    }
+
+    pub fn support_vectors(&self) -> &[T] {
+        &self.support_vectors
+    }
+
+    /// `y_i α_i` for each support vector.
+    pub fn dual_coefficients(&self) -> &[f64] {
+        &self.dual_coefficients
+    }
+
+    pub fn intercept(&self) -> f64 {
+        self.intercept
+    }
+
+    /// Whether SMO reached `SmoOptions::tolerance` within `SmoOptions::max_iterations`.
+    /// Otherwise the model is built from the last iterate.
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    pub fn decision_function(&self, x: &[T]) -> Result<Vec<f64>, KernelError> {
+        x.par_iter()
+            .map(|xi| {
+                let fx = self
+                    .support_vectors
+                    .iter()
+                    .zip(self.dual_coefficients.iter())
+                    .map(|(sv, coef)| Ok(coef * self.kernel.value(&self.params, sv, xi)?))
+                    .sum::<Result<f64, KernelError>>()?;
+
+                Ok(fx + self.intercept)
+            })
+            .collect()
+    }
+
+    /// `±1` by the sign of the decision function.
+    pub fn predict(&self, x: &[T]) -> Result<Vec<f64>, KernelError> {
+        Ok(self
+            .decision_function(x)?
+            .into_iter()
+            .map(|fx| if fx >= 0.0 { 1.0 } else { -1.0 })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::prelude::*;
+
+    fn blobs(seed: u64) -> (Vec<Vec<f64>>, Vec<f64>) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..80)
+            .map(|i| {
+                let label = if i % 2 == 0 { 1.0 } else { -1.0 };
+                let x = vec![
+                    label + rng.gen_range(-0.8..0.8),
+                    label + rng.gen_range(-0.8..0.8),
+                ];
+                (x, label)
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn it_works() {
+        let (x, y) = blobs(1);
+        let c = 10.0;
+
+        let svc =
+            SupportVectorClassifier::fit(Linear, &[], x.clone(), &y, c, &SmoOptions::default())
+                .unwrap();
+        let fx = svc.decision_function(&x).unwrap();
+
+        assert!(svc.dual_coefficients().iter().sum::<f64>().abs() < 1e-10);
+        assert!(svc.support_vectors().len() < x.len());
+        for (i, &coef) in svc.support_indices().iter().zip(svc.dual_coefficients()) {
+            assert!(coef * y[*i] > 0.0 && coef * y[*i] <= c);
+            if coef.abs() < c {
+                assert!((y[*i] * fx[*i] - 1.0).abs() < 1e-2);
+            }
+        }
+        assert_eq!(svc.predict(&x).unwrap(), y);
+        assert!(svc.converged());
+
+        let options = SmoOptions {
+            shrinking: false,
+            cache_rows: 2,
+            ..Default::default()
+        };
+        let plain = SupportVectorClassifier::fit(Linear, &[], x.clone(), &y, c, &options).unwrap();
+        assert!(plain.converged());
+        let plain_fx = plain.decision_function(&x).unwrap();
+        for (a, b) in fx.iter().zip(plain_fx.iter()) {
+            assert!((a - b).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn it_works2() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let x = (0..100)
+            .map(|_| vec![rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)])
+            .collect::<Vec<Vec<f64>>>();
+        let y = x
+            .iter()
+            .map(|x| if x[0] * x[1] > 0.0 { 1.0 } else { -1.0 })
+            .collect::<Vec<f64>>();
+        let kernel = DeepNeuralNetwork::builder().layers(3, ReLU).build();
+        let params = [0.5, 1.0, 0.5, 2.0, 0.5, 2.0, 0.5, 2.0];
+
+        let svc = SupportVectorClassifier::fit(
+            kernel,
+            &params,
+            x.clone(),
+            &y,
+            100.0,
+            &SmoOptions::default(),
+        )
+        .unwrap();
+        let accuracy = svc
+            .predict(&x)
+            .unwrap()
+            .iter()
+            .zip(y.iter())
+            .filter(|(p, y)| p == y)
+            .count();
+
+        assert!(accuracy >= 90, "{}", accuracy);
+        let options = SmoOptions {
+            max_iterations: 1,
+            ..Default::default()
+        };
+        let stopped =
+            SupportVectorClassifier::fit(RBF, &[1.0, 1.0], x.clone(), &y, 1.0, &options).unwrap();
+        assert!(!stopped.converged());
+        match SupportVectorClassifier::fit(
+            RBF,
+            &[1.0, 1.0],
+            x.clone(),
+            &vec![0.0; 100],
+            1.0,
+            &SmoOptions::default(),
+        ) {
+            Err(KernelError::InvalidArgument) => (),
+            _ => panic!(),
+        };
+        match SupportVectorClassifier::fit(
+            RBF,
+            &[1.0, 1.0],
+            x.clone(),
+            &vec![1.0; 100],
+            1.0,
+            &SmoOptions::default(),
+        ) {
+            Err(KernelError::InvalidArgument) => (),
+            _ => panic!(),
+        };
+    }
+}
@@ -0,0 +1,185 @@
+use super::solver::{solve, Problem};
+use super::SmoOptions;
+use crate::{KernelError, PositiveDefiniteKernel, Value};
+use rayon::prelude::*;
+
+/// ε-support vector regression, solved by SMO over the `2n` variables `(α, α*)`.
+///
+/// The prediction is `Σ_i coef_i k(sv_i, x) + b` over the support vectors, with `coef_i = α_i - α*_i`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SupportVectorRegressor<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    kernel: K,
+    params: Vec<f64>,
+    support_indices: Vec<usize>,
+    support_vectors: Vec<T>,
+    dual_coefficients: Vec<f64>,
+    intercept: f64,
+    converged: bool,
+}
+
+impl<K, T> SupportVectorRegressor<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    pub fn fit(
+        kernel: K,
+        params: &[f64],
+        x: Vec<T>,
+        y: &[f64],
+        c: f64,
+        epsilon: f64,
+        options: &SmoOptions,
+    ) -> Result<Self, KernelError> {
+        if x.len() != y.len() || x.is_empty() {
+            return Err(KernelError::InvalidArgument);
+        }
+        if c.is_nan() || c <= 0.0 || epsilon.is_nan() || epsilon < 0.0 {
+            return Err(KernelError::InvalidParameter);
+        }
+
+        let l = x.len();
+        let problem = Problem {
+            index: (0..2 * l).map(|t| t % l).collect(),
+            y: (0..2 * l).map(|t| if t < l { 1.0 } else { -1.0 }).collect(),
+            p: (0..2 * l)
+                .map(|t| {
+                    if t < l {
+                        epsilon - y[t]
+                    } else {
+                        epsilon + y[t - l]
+                    }
+                })
+                .collect(),
+            c: vec![c; 2 * l],
+        };
+        let solution = solve(&kernel, params, &x, problem, options)?;
+
+        let coefficients = (0..l)
+            .map(|i| solution.alpha[i] - solution.alpha[i + l])
+            .collect::<Vec<f64>>();
+        let support_indices = (0..l)
+            .filter(|&i| coefficients[i] != 0.0)
+            .collect::<Vec<usize>>();
+
+        Ok(Self {
+            support_vectors: support_indices.iter().map(|&i| x[i].clone()).collect(),
+            dual_coefficients: support_indices.iter().map(|&i| coefficients[i]).collect(),
+            support_indices,
+            intercept: -solution.rho,
+            converged: solution.converged,
+            kernel,
+            params: params.to_vec(),
+        })
+    }
+
+    pub fn kernel_ref(&self) -> &K {
+        &self.kernel
+    }
+
+    pub fn params(&self) -> &[f64] {
+        &self.params
+    }
+
+    /// Indices of the support vectors in the training set.
+    pub fn support_indices(&self) -> &[usize] {
+        &self.support_indices
+    }
+
+    pub fn support_vectors(&self) -> &[T] {
+        &self.support_vectors
+    }
+
+    /// `α_i - α*_i` for each support vector.
+    pub fn dual_coefficients(&self) -> &[f64] {
+        &self.dual_coefficients
+    }
+
+    pub fn intercept(&self) -> f64 {
+        self.intercept
+    }
+
+    /// Whether SMO reached `SmoOptions::tolerance` within `SmoOptions::max_iterations`.
+    /// Otherwise the model is built from the last iterate.
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    pub fn predict(&self, x: &[T]) -> Result<Vec<f64>, KernelError> {
+        x.par_iter()
+            .map(|xi| {
+                let fx = self
+                    .support_vectors
+                    .iter()
+                    .zip(self.dual_coefficients.iter())
+                    .map(|(sv, coef)| Ok(coef * self.kernel.value(&self.params, sv, xi)?))
+                    .sum::<Result<f64, KernelError>>()?;
+
+                Ok(fx + self.intercept)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::prelude::*;
+
+    #[test]
+    fn it_works() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let x = (0..60)
+            .map(|_| vec![rng.gen_range(-3.0..3.0)])
+            .collect::<Vec<Vec<f64>>>();
+        let y = x
+            .iter()
+            .map(|x| x[0].sin() + 0.05 * rng.gen_range(-1.0..1.0))
+            .collect::<Vec<f64>>();
+        let (c, epsilon) = (10.0, 0.1);
+
+        let svr = SupportVectorRegressor::fit(
+            RBF,
+            &[1.0, 1.0],
+            x.clone(),
+            &y,
+            c,
+            epsilon,
+            &SmoOptions::default(),
+        )
+        .unwrap();
+        let fx = svr.predict(&x).unwrap();
+
+        assert!(svr.dual_coefficients().iter().sum::<f64>().abs() < 1e-10);
+        assert!(svr.support_vectors().len() < x.len());
+        for (i, &coef) in svr.support_indices().iter().zip(svr.dual_coefficients()) {
+            assert!(coef.abs() <= c);
+            if coef.abs() < c {
+                assert!(((y[*i] - fx[*i]).abs() - epsilon).abs() < 1e-2);
+            }
+        }
+        for i in 0..x.len() {
+            if !svr.support_indices().contains(&i) {
+                assert!((y[i] - fx[i]).abs() <= epsilon + 1e-2);
+            }
+        }
+
+        let prediction = svr.predict(&[vec![1.0]]).unwrap();
+        assert!((prediction[0] - 1f64.sin()).abs() < 0.15);
+        assert!(svr.converged());
+
+        let options = SmoOptions {
+            max_iterations: 1,
+            ..Default::default()
+        };
+        let stopped =
+            SupportVectorRegressor::fit(RBF, &[1.0, 1.0], x.clone(), &y, c, epsilon, &options)
+                .unwrap();
+        assert!(!stopped.converged());
+    }
+}
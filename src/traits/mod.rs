@@ -1,5 +1,7 @@
 pub mod params_differentiable;
+pub mod stationary;
 pub mod value_differentiable;
 
 pub use params_differentiable::*;
-pub use params_differentiable::*;
+pub use stationary::*;
+pub use value_differentiable::*;
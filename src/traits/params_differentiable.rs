@@ -1,8 +1,45 @@
 use crate::{KernelError, PositiveDefiniteKernel, Value};
+use opensrdk_linear_algebra::Matrix;
+use rayon::prelude::*;
 
 pub trait ParamsDifferentiableKernel<T>: PositiveDefiniteKernel<T>
 where
     T: Value,
 {
     fn ln_diff_params(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError>;
+
+    /// Whether `ln_diff_params` succeeds. Only type-erased kernels and the composites and wrappers
+    /// around them can be `false`, and those take it from their children.
+    fn is_params_differentiable(&self) -> bool {
+        true
+    }
+
+    /// `∂K(X, X) / ∂params[j]` for each `j`. Only the upper triangle is evaluated and mirrored.
+    fn gram_matrix_diff_params(&self, params: &[f64], x: &[T]) -> Result<Vec<Matrix>, KernelError> {
+        let n = x.len();
+        let upper = (0..n)
+            .into_par_iter()
+            .map(|j| {
+                (0..=j)
+                    .map(|i| {
+                        let k = self.value(params, &x[i], &x[j])?;
+                        let diff = self.ln_diff_params(params, &x[i], &x[j])?;
+                        Ok(diff.into_iter().map(|d| k * d).collect::<Vec<f64>>())
+                    })
+                    .collect::<Result<Vec<Vec<f64>>, KernelError>>()
+            })
+            .collect::<Result<Vec<Vec<Vec<f64>>>, KernelError>>()?;
+
+        let mut dk = vec![Matrix::new(n, n); self.params_len()];
+        for (j, column) in upper.into_iter().enumerate() {
+            for (i, diff) in column.into_iter().enumerate() {
+                for (dk, d) in dk.iter_mut().zip(diff) {
+                    dk[(i, j)] = d;
+                    dk[(j, i)] = d;
+                }
+            }
+        }
+
+        Ok(dk)
+    }
 }
@@ -0,0 +1,119 @@
+use crate::special::ln_gamma;
+use crate::{KernelError, PositiveDefiniteKernel};
+use rand::Rng;
+use rand_distr::{ChiSquared, Distribution, StandardNormal};
+use std::f64::consts::PI;
+
+/// Kernel of the form `k(x, x') = k(x - x')`.
+///
+/// By Bochner's theorem `k(τ) = ∫ S(ω) cos(ω · τ) dω` for a non-negative spectral density `S`
+/// whose total mass is `k(0)`.
+pub trait StationaryKernel: PositiveDefiniteKernel<Vec<f64>> {
+    /// `k(0)`, the total mass of the spectral density.
+    fn variance(&self, params: &[f64]) -> Result<f64, KernelError>;
+
+    /// `S(ω)` with respect to the angular frequency `ω`.
+    fn spectral_density(&self, params: &[f64], omega: &[f64]) -> Result<f64, KernelError>;
+
+    /// Draws a `dims`-dimensional frequency from `S(ω) / k(0)`.
+    fn sample_frequency<R>(
+        &self,
+        params: &[f64],
+        dims: usize,
+        rng: &mut R,
+    ) -> Result<Vec<f64>, KernelError>
+    where
+        R: Rng + ?Sized;
+
+    /// Whether `k` depends on `x - x'` only through its norm, so that `S` is rotation invariant.
+    fn is_isotropic(&self) -> bool {
+        false
+    }
+}
+
+/// Frequency of a kernel whose spectral density is the multivariate Student's t distribution
+/// with `dof` degrees of freedom and scale `1 / lengthscale`, such as the Matérn kernel with `dof = 2ν`.
+pub(crate) fn sample_student_t<R>(
+    dof: f64,
+    lengthscale: f64,
+    dims: usize,
+    rng: &mut R,
+) -> Result<Vec<f64>, KernelError>
+where
+    R: Rng + ?Sized,
+{
+    let chi_squared = ChiSquared::new(dof).map_err(|_| KernelError::InvalidParameter)?;
+    let scale = (dof / chi_squared.sample(rng)).sqrt() / lengthscale;
+
+    Ok((0..dims)
+        .map(|_| scale * rng.sample::<f64, _>(StandardNormal))
+        .collect())
+}
+
+/// Density of `sample_student_t`.
+pub(crate) fn student_t_density(dof: f64, lengthscale: f64, omega: &[f64]) -> f64 {
+    let dims = omega.len() as f64;
+    let norm_pow = omega.iter().map(|w| (w * lengthscale).powi(2)).sum::<f64>();
+
+    let ln_density =
+        ln_gamma((dof + dims) / 2.0) - ln_gamma(dof / 2.0) - dims / 2.0 * (dof * PI).ln()
+            + dims * lengthscale.ln()
+            - (dof + dims) / 2.0 * (norm_pow / dof).ln_1p();
+
+    ln_density.exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    /// `∫ S(ω) cos(ω τ) dω` on a grid.
+    fn inverse_transform<K>(kernel: &K, params: &[f64], tau: f64, limit: f64) -> f64
+    where
+        K: StationaryKernel,
+    {
+        let steps = 400000;
+        let h = 2.0 * limit / steps as f64;
+
+        (0..=steps)
+            .map(|i| {
+                let omega = -limit + h * i as f64;
+                let weight = if i == 0 || i == steps { 0.5 } else { 1.0 };
+                weight
+                    * h
+                    * kernel.spectral_density(params, &[omega]).unwrap()
+                    * (omega * tau).cos()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn it_works() {
+        let tau = 0.7;
+        let x = vec![tau];
+        let xprime = vec![0.0];
+
+        let rbf = inverse_transform(&RBF, &[1.5, 0.8], tau, 50.0);
+        let exponential = inverse_transform(&Exponential, &[0.6], tau, 5000.0);
+        let matern = inverse_transform(&Matern::new(1.5), &[1.2, 0.7], tau, 500.0);
+        let params = [0.6, 0.4, 0.3, 0.2, 0.5, 0.1];
+        let spectral_mixture = inverse_transform(&SpectralMixture::new(1, 2), &params, tau, 50.0);
+
+        let expected = |k: f64, value: f64| (k - value).abs() < 1e-3;
+        assert!(expected(rbf, RBF.value(&[1.5, 0.8], &x, &xprime).unwrap()));
+        assert!(expected(
+            exponential,
+            Exponential.value(&[0.6], &x, &xprime).unwrap()
+        ));
+        assert!(expected(
+            matern,
+            Matern::new(1.5).value(&[1.2, 0.7], &x, &xprime).unwrap()
+        ));
+        assert!(expected(
+            spectral_mixture,
+            SpectralMixture::new(1, 2)
+                .value(&params, &x, &xprime)
+                .unwrap()
+        ));
+    }
+}
@@ -5,4 +5,9 @@ where
     T: Value,
 {
     fn ln_diff_value(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError>;
+
+    /// Whether `ln_diff_value` succeeds, with the same meaning as `is_params_differentiable`.
+    fn is_value_differentiable(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,330 @@
+use crate::{
+    KernelAdd, KernelError, KernelMul, ParamsDifferentiableKernel, PositiveDefiniteKernel, Value,
+    ValueDifferentiableKernel,
+};
+use std::marker::PhantomData;
+use std::{ops::Add, ops::Mul};
+
+/// Bijection from an unconstrained optimizer variable `u` to a kernel parameter.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParamsTransform {
+    Identity,
+    /// `exp(u)`, onto `(0, ∞)`
+    Exp,
+    /// `ln(1 + exp(u))`, onto `(0, ∞)`
+    Softplus,
+    /// `lower + (upper - lower) * sigmoid(u)`, onto `(lower, upper)`. The bounds need to be finite with `lower < upper`.
+    Interval {
+        lower: f64,
+        upper: f64,
+    },
+}
+
+fn sigmoid(u: f64) -> f64 {
+    1.0 / (1.0 + (-u).exp())
+}
+
+impl ParamsTransform {
+    /// Maps an unconstrained value to the parameter.
+    pub fn forward(&self, u: f64) -> f64 {
+        match *self {
+            ParamsTransform::Identity => u,
+            ParamsTransform::Exp => u.exp(),
+            ParamsTransform::Softplus => u.max(0.0) + (-u.abs()).exp().ln_1p(),
+            ParamsTransform::Interval { lower, upper } => lower + (upper - lower) * sigmoid(u),
+        }
+    }
+
+    /// Maps a parameter back to the unconstrained space.
+    pub fn inverse(&self, param: f64) -> Result<f64, KernelError> {
+        match *self {
+            ParamsTransform::Identity => Ok(param),
+            ParamsTransform::Exp if param > 0.0 => Ok(param.ln()),
+            ParamsTransform::Softplus if param > 0.0 => Ok(param + (-(-param).exp()).ln_1p()),
+            ParamsTransform::Interval { lower, upper } if lower < param && param < upper => {
+                Ok(((param - lower) / (upper - param)).ln())
+            }
+            _ => Err(KernelError::InvalidParameter),
+        }
+    }
+
+    /// Derivative of `forward` at `u`.
+    pub fn diff(&self, u: f64) -> f64 {
+        match *self {
+            ParamsTransform::Identity => 1.0,
+            ParamsTransform::Exp => u.exp(),
+            ParamsTransform::Softplus => sigmoid(u),
+            ParamsTransform::Interval { lower, upper } => {
+                let s = sigmoid(u);
+                (upper - lower) * s * (1.0 - s)
+            }
+        }
+    }
+}
+
+/// Wraps a kernel so that its params are given in an unconstrained space.
+///
+/// `value` maps each param through its `ParamsTransform` before calling the inner kernel, and
+/// `ln_diff_params` returns the gradient with respect to the unconstrained params.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transformed<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    kernel: K,
+    transforms: Vec<ParamsTransform>,
+    phantom: PhantomData<T>,
+}
+
+impl<K, T> Transformed<K, T>
+where
+    K: PositiveDefiniteKernel<T>,
+    T: Value,
+{
+    /// Needs one transform per param of `kernel`.
+    pub fn new(kernel: K, transforms: Vec<ParamsTransform>) -> Result<Self, KernelError> {
+        if transforms.len() != kernel.params_len() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+        let is_valid = |t: &ParamsTransform| match *t {
+            ParamsTransform::Interval { lower, upper } => {
+                lower.is_finite() && upper.is_finite() && lower < upper
+            }
+            _ => true,
+        };
+        if !transforms.iter().all(is_valid) {
+            return Err(KernelError::InvalidParameter);
+        }
+
+        Ok(Self {
+            kernel,
+            transforms,
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn kernel_ref(&self) -> &K {
+        &self.kernel
+    }
+
+    pub fn transforms(&self) -> &[ParamsTransform] {
+        &self.transforms
+    }
+
+    /// Params of the inner kernel for the given unconstrained params.
+    pub fn constrained_params(&self, params: &[f64]) -> Result<Vec<f64>, KernelError> {
+        if params.len() != self.transforms.len() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+
+        Ok(self
+            .transforms
+            .iter()
+            .zip(params.iter())
+            .map(|(t, &u)| t.forward(u))
+            .collect())
+    }
+
+    /// Unconstrained params for the given params of the inner kernel, e.g. to initialize an optimizer.
+    pub fn unconstrained_params(&self, params: &[f64]) -> Result<Vec<f64>, KernelError> {
+        if params.len() != self.transforms.len() {
+            return Err(KernelError::ParametersLengthMismatch);
+        }
+
+        self.transforms
+            .iter()
+            .zip(params.iter())
+            .map(|(t, &param)| t.inverse(param))
+            .collect()
+    }
+}
+
+impl<T, K> PositiveDefiniteKernel<T> for Transformed<K, T>
+where
+    T: Value,
+    K: PositiveDefiniteKernel<T>,
+{
+    fn params_len(&self) -> usize {
+        self.kernel.params_len()
+    }
+
+    fn params_names(&self) -> Vec<String> {
+        self.kernel.params_names()
+    }
+
+    fn value(&self, params: &[f64], x: &T, xprime: &T) -> Result<f64, KernelError> {
+        let params = self.constrained_params(params)?;
+
+        self.kernel.value(&params, x, xprime)
+    }
+}
+
+impl<K, R> Add<R> for Transformed<K, Vec<f64>>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelAdd<Self, R, Vec<f64>>;
+
+    fn add(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<K, R> Mul<R> for Transformed<K, Vec<f64>>
+where
+    K: PositiveDefiniteKernel<Vec<f64>>,
+    R: PositiveDefiniteKernel<Vec<f64>>,
+{
+    type Output = KernelMul<Self, R, Vec<f64>>;
+
+    fn mul(self, rhs: R) -> Self::Output {
+        Self::Output::new(self, rhs)
+    }
+}
+
+impl<T, K> ValueDifferentiableKernel<T> for Transformed<K, T>
+where
+    T: Value,
+    K: ValueDifferentiableKernel<T>,
+{
+    fn ln_diff_value(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError> {
+        let params = self.constrained_params(params)?;
+
+        self.kernel.ln_diff_value(&params, x, xprime)
+    }
+
+    fn is_value_differentiable(&self) -> bool {
+        self.kernel.is_value_differentiable()
+    }
+}
+
+impl<T, K> ParamsDifferentiableKernel<T> for Transformed<K, T>
+where
+    T: Value,
+    K: ParamsDifferentiableKernel<T>,
+{
+    fn ln_diff_params(&self, params: &[f64], x: &T, xprime: &T) -> Result<Vec<f64>, KernelError> {
+        let constrained = self.constrained_params(params)?;
+
+        let diff = self
+            .kernel
+            .ln_diff_params(&constrained, x, xprime)?
+            .iter()
+            .zip(self.transforms.iter().zip(params.iter()))
+            .map(|(diff_param, (t, &u))| diff_param * t.diff(u))
+            .collect();
+
+        Ok(diff)
+    }
+
+    fn is_params_differentiable(&self) -> bool {
+        self.kernel.is_params_differentiable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let kernel =
+            Transformed::new(RBF, vec![ParamsTransform::Exp, ParamsTransform::Softplus]).unwrap();
+
+        let test_value = kernel
+            .value(&[0.0, -30.0], &vec![0.0, 0.0], &vec![0.0, 0.0])
+            .unwrap();
+
+        assert_eq!(test_value, 1.0);
+        match kernel.value(&[0.0], &vec![0.0], &vec![0.0]) {
+            Err(KernelError::ParametersLengthMismatch) => (),
+            _ => panic!(),
+        };
+        match Transformed::new(RBF, vec![ParamsTransform::Exp]) {
+            Err(KernelError::ParametersLengthMismatch) => (),
+            _ => panic!(),
+        };
+        for (lower, upper) in [
+            (1.0, 0.0),
+            (1.0, 1.0),
+            (0.0, f64::INFINITY),
+            (f64::NAN, 1.0),
+        ] {
+            match Transformed::new(
+                RBF,
+                vec![
+                    ParamsTransform::Exp,
+                    ParamsTransform::Interval { lower, upper },
+                ],
+            ) {
+                Err(KernelError::InvalidParameter) => (),
+                _ => panic!(),
+            };
+        }
+    }
+
+    #[test]
+    fn it_works2() {
+        let transforms = vec![
+            ParamsTransform::Identity,
+            ParamsTransform::Exp,
+            ParamsTransform::Softplus,
+            ParamsTransform::Interval {
+                lower: 1.0,
+                upper: 3.0,
+            },
+        ];
+        let kernel = Transformed::new(ARD(4), transforms.clone()).unwrap();
+
+        let params = [-0.5, 0.2, 4.0, 2.5];
+        let u = kernel.unconstrained_params(&params).unwrap();
+        let back = kernel.constrained_params(&u).unwrap();
+
+        for (p, b) in params.iter().zip(back.iter()) {
+            assert!((p - b).abs() < 1e-12);
+        }
+        for t in transforms.iter() {
+            let h = 1e-6;
+            let numerical = (t.forward(0.3 + h) - t.forward(0.3 - h)) / (2.0 * h);
+            assert!((t.diff(0.3) - numerical).abs() < 1e-8);
+        }
+        assert!(ParamsTransform::Exp.inverse(-1.0).is_err());
+        assert!(ParamsTransform::Interval {
+            lower: 1.0,
+            upper: 3.0
+        }
+        .inverse(3.0)
+        .is_err());
+    }
+
+    #[test]
+    fn it_works3() {
+        let kernel = Transformed::new(
+            RBF + Constant * Periodic,
+            vec![
+                ParamsTransform::Exp,
+                ParamsTransform::Softplus,
+                ParamsTransform::Exp,
+                ParamsTransform::Identity,
+                ParamsTransform::Interval {
+                    lower: 0.5,
+                    upper: 2.0,
+                },
+            ],
+        )
+        .unwrap();
+        let params = [0.3, -0.2, 0.1, 0.7, 0.4];
+        let x = vec![0.1, 0.5, -0.3];
+        let xprime = vec![0.4, -0.2, 0.8];
+
+        let params_check = check_params_gradient(&kernel, &params, &x, &xprime).unwrap();
+        let value_check = check_value_gradient(&kernel, &params, &x, &xprime).unwrap();
+
+        assert!(params_check.max_error() < 1e-6, "{:?}", params_check);
+        assert!(value_check.max_error() < 1e-6, "{:?}", value_check);
+    }
+}
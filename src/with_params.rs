@@ -0,0 +1,70 @@
+use crate::{KernelError, PositiveDefiniteKernel, Value};
+
+/// A kernel together with its params, e.g. fitted hyperparameters to be saved and reloaded.
+///
+/// With the `serde` feature this serializes both the kernel structure and the params vector,
+/// so `value` of a deserialized kernel matches the original exactly.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KernelWithParams<K> {
+    pub kernel: K,
+    pub params: Vec<f64>,
+}
+
+impl<K> KernelWithParams<K> {
+    pub fn new(kernel: K, params: Vec<f64>) -> Self {
+        Self { kernel, params }
+    }
+
+    pub fn value<T>(&self, x: &T, xprime: &T) -> Result<f64, KernelError>
+    where
+        T: Value,
+        K: PositiveDefiniteKernel<T>,
+    {
+        self.kernel.value(&self.params, x, xprime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn it_works() {
+        let kernel = KernelWithParams::new(RBF + Constant, vec![1.0, 0.5, 2.0]);
+
+        let test_value = kernel.value(&vec![0.0, 0.0], &vec![0.0, 0.0]).unwrap();
+
+        assert_eq!(test_value, 3.0);
+    }
+
+    #[cfg(feature = "serde")]
+    type Kernel = Transformed<
+        KernelAdd<RBF, KernelMul<SpectralMixture, Matern, Vec<f64>>, Vec<f64>>,
+        Vec<f64>,
+    >;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let kernel = KernelWithParams::new(
+            Transformed::new(
+                RBF + SpectralMixture::new(2, 1) * Matern::new(1.5),
+                vec![ParamsTransform::Exp; 9],
+            )
+            .unwrap(),
+            vec![0.1, -0.3, 0.7, 1.0 / 3.0, 0.2, -1.7, 0.9, 0.05, -0.6],
+        );
+        let x = vec![0.3, -0.2];
+        let xprime = vec![-0.4, 0.5];
+
+        let json = serde_json::to_string(&kernel).unwrap();
+        let restored: KernelWithParams<Kernel> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.params, kernel.params);
+        assert_eq!(
+            restored.value(&x, &xprime).unwrap().to_bits(),
+            kernel.value(&x, &xprime).unwrap().to_bits()
+        );
+    }
+}